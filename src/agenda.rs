@@ -0,0 +1,451 @@
+use crate::book::Hit;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::fmt::Write;
+
+// Markdown agenda with a heading per day and a bullet per appointment. Appointments are listed
+// under the day they start, and owners are named when there's more than one
+pub fn markdown(hits: &[Hit]) -> String {
+    let mut out = String::new();
+    let several_owners = hits
+        .iter()
+        .any(|hit| hit.owner.handle != hits[0].owner.handle);
+
+    let mut hits: Vec<&Hit> = hits.iter().collect();
+    hits.sort_by_key(|hit| hit.appt.start_date_time);
+
+    let mut current_day = None;
+
+    for hit in hits {
+        let day = hit.appt.start_date_time.date();
+
+        if current_day != Some(day) {
+            if current_day.is_some() {
+                out.push('\n');
+            }
+
+            writeln!(out, "## {}\n", day.format("%A, %m/%d/%Y")).unwrap();
+            current_day = Some(day);
+        }
+
+        let times = if hit.appt.all_day && day == hit.appt.last_day() {
+            "All day".to_string()
+        } else if hit.appt.all_day {
+            format!("All day to {}", hit.appt.last_day().format("%m/%d/%Y"))
+        } else if day == hit.appt.end_date_time.date() {
+            format!(
+                "{}-{}",
+                hit.appt.start_date_time.format("%H:%M"),
+                hit.appt.end_date_time.format("%H:%M")
+            )
+        } else {
+            format!(
+                "{}-{}",
+                hit.appt.start_date_time.format("%H:%M"),
+                hit.appt.end_date_time.format("%m/%d/%Y %H:%M")
+            )
+        };
+
+        write!(
+            out,
+            "- {} {} ({})",
+            times,
+            escape_markdown(&hit.appt.description),
+            hit.appt.formatted_duration()
+        )
+        .unwrap();
+
+        if several_owners {
+            write!(out, " - {}", escape_markdown(&hit.owner.name)).unwrap();
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+// Org-mode outline with a heading per owner and an entry per appointment, each with an active
+// timestamp Org's agenda picks up
+pub fn org(hits: &[Hit]) -> String {
+    let mut out = String::new();
+    let mut current_owner: Option<&str> = None;
+
+    for hit in hits {
+        if current_owner != Some(&hit.owner.handle) {
+            writeln!(out, "* {}", hit.owner.name).unwrap();
+            current_owner = Some(&hit.owner.handle);
+        }
+
+        let start = hit.appt.start_date_time;
+        let end = hit.appt.end_date_time;
+
+        // All-day appointments use dates without times. Same-day appointments use a time range
+        // within one timestamp, others a date range
+        let timestamp = if hit.appt.all_day && start.date() == hit.appt.last_day() {
+            format!("<{}>", start.format("%Y-%m-%d %a"))
+        } else if hit.appt.all_day {
+            format!(
+                "<{}>--<{}>",
+                start.format("%Y-%m-%d %a"),
+                hit.appt.last_day().format("%Y-%m-%d %a")
+            )
+        } else if start.date() == end.date() {
+            format!(
+                "<{}-{}>",
+                start.format("%Y-%m-%d %a %H:%M"),
+                end.format("%H:%M")
+            )
+        } else {
+            format!(
+                "<{}>--<{}>",
+                start.format("%Y-%m-%d %a %H:%M"),
+                end.format("%Y-%m-%d %a %H:%M")
+            )
+        };
+
+        // Headlines end at the line break, so keep descriptions to one line
+        let description = hit.appt.description.replace('\n', " ");
+
+        // Org tags can't contain dashes
+        let tags = if hit.appt.tags.is_empty() {
+            String::new()
+        } else {
+            format!(" :{}:", hit.appt.tags.join(":").replace('-', "_"))
+        };
+
+        writeln!(out, "** {}{}\n   {}", description, tags, timestamp).unwrap();
+    }
+
+    out
+}
+
+// The appointments on a day, including those continuing from or into other days. Appointments
+// that are over or in progress at now are marked, as are those that aren't confirmed, and owners
+// are named when there's more than one
+pub fn day(hits: &[Hit], day: NaiveDate, now: NaiveDateTime) -> String {
+    let mut out = String::new();
+    writeln!(out, "{}", day.format("%A, %m/%d/%Y")).unwrap();
+
+    let hits = on_day(hits, day);
+
+    if hits.is_empty() {
+        out.push_str("  No appointments\n");
+        return out;
+    }
+
+    let several_owners = hits
+        .iter()
+        .any(|hit| hit.owner.handle != hits[0].owner.handle);
+
+    // Times on other days include the date
+    let time = |date_time: NaiveDateTime| {
+        if date_time.date() == day {
+            date_time.format("%H:%M").to_string()
+        } else {
+            date_time.format("%m/%d %H:%M").to_string()
+        }
+    };
+
+    for hit in hits {
+        let times = if hit.appt.all_day {
+            "All day".to_string()
+        } else {
+            format!(
+                "{}-{}",
+                time(hit.appt.start_date_time),
+                time(hit.appt.end_date_time)
+            )
+        };
+
+        write!(
+            out,
+            "  {}  {}",
+            times,
+            hit.appt.description.replace('\n', " ")
+        )
+        .unwrap();
+
+        if several_owners {
+            write!(out, " - {}", hit.owner.name).unwrap();
+        }
+
+        if !hit.appt.status.is_confirmed() {
+            write!(out, "  ({})", hit.appt.status.name()).unwrap();
+        } else if hit.appt.end_date_time <= now {
+            out.push_str("  (done)");
+        } else if hit.appt.start_date_time <= now {
+            out.push_str("  (now)");
+        }
+
+        out.push('\n');
+    }
+
+    out
+}
+
+// The appointments on a day, including those continuing from or into other days, earliest first
+pub fn on_day<'a>(hits: &[Hit<'a>], day: NaiveDate) -> Vec<Hit<'a>> {
+    let mut hits: Vec<Hit> = hits
+        .iter()
+        .filter(|hit| hit.appt.occurs_on(day))
+        .copied()
+        .collect();
+    hits.sort_by_key(|hit| hit.appt.start_date_time);
+    hits
+}
+
+// The next appointment to start after now, with a countdown. Appointments starting at the same
+// time are all shown. Cancelled appointments are skipped
+pub fn next(hits: &[Hit], now: NaiveDateTime) -> String {
+    let hits = upcoming(hits, now);
+
+    let start = match hits.first() {
+        Some(hit) => hit.appt.start_date_time,
+        None => return "No upcoming appointments\n".to_string(),
+    };
+
+    let mut out = String::new();
+
+    for hit in &hits {
+        writeln!(out, "> {} ({})", hit.appt.description, hit.owner.name).unwrap();
+        writeln!(out, "| {}", hit.appt.formatted_span()).unwrap();
+
+        if !hit.appt.status.is_confirmed() {
+            writeln!(out, "| Status: {}", hit.appt.status.name()).unwrap();
+        }
+    }
+
+    writeln!(out, "Starts {}", countdown(start - now)).unwrap();
+
+    out
+}
+
+// The appointments starting soonest after now, leaving out cancelled ones. There's more than one
+// when several start at the same time
+pub fn upcoming<'a>(hits: &[Hit<'a>], now: NaiveDateTime) -> Vec<Hit<'a>> {
+    let start = hits
+        .iter()
+        .filter(|hit| !hit.appt.is_cancelled())
+        .map(|hit| hit.appt.start_date_time)
+        .filter(|start| *start > now)
+        .min();
+
+    hits.iter()
+        .filter(|hit| !hit.appt.is_cancelled() && Some(hit.appt.start_date_time) == start)
+        .copied()
+        .collect()
+}
+
+// How long until something happens, to the minute, e.g. "in 2 days 3 hours"
+pub fn countdown(duration: Duration) -> String {
+    if duration.num_minutes() < 1 {
+        return "in less than a minute".to_string();
+    }
+
+    format!("in {}", length(duration))
+}
+
+// A length of time to the minute, e.g. "2 days 3 hours". Only the two largest units are worth
+// reading
+pub fn length(duration: Duration) -> String {
+    let minutes = duration.num_minutes();
+
+    let unit = |n: i64, name: &str| {
+        if n == 1 {
+            format!("1 {}", name)
+        } else {
+            format!("{} {}s", n, name)
+        }
+    };
+
+    let (days, hours, minutes) = (minutes / (24 * 60), minutes / 60 % 24, minutes % 60);
+
+    let parts = if days > 0 {
+        vec![unit(days, "day"), unit(hours, "hour")]
+    } else if hours > 0 {
+        vec![unit(hours, "hour"), unit(minutes, "minute")]
+    } else {
+        return unit(minutes, "minute");
+    };
+
+    let parts: Vec<String> = parts
+        .into_iter()
+        .filter(|part| !part.starts_with("0 "))
+        .collect();
+
+    parts.join(" ")
+}
+
+// Backslash characters Markdown would otherwise treat as formatting
+fn escape_markdown(text: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in text.chars() {
+        if "\\`*_[]<>#|".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn sample_book() -> crate::book::Book {
+    crate::book::Book::sample(&[
+        (
+            "Alice",
+            "Review *draft*",
+            "10/18/2026 10:00",
+            "10/18/2026 11:00",
+        ),
+        ("Alice", "Retreat", "10/19/2026 09:00", "10/20/2026 17:00"),
+        ("Bob", "Lunch", "10/18/2026 12:00", "10/18/2026 12:30"),
+    ])
+}
+
+#[test]
+fn markdown_agenda() {
+    let apptbook = sample_book();
+
+    assert_eq!(
+        markdown(&apptbook.select(|_, _| true)),
+        "## Sunday, 10/18/2026\n\n\
+         - 10:00-11:00 Review \\*draft\\* (60 minutes) - Alice\n\
+         - 12:00-12:30 Lunch (30 minutes) - Bob\n\
+         \n\
+         ## Monday, 10/19/2026\n\n\
+         - 09:00-10/20/2026 17:00 Retreat (1920 minutes) - Alice\n"
+    );
+
+    let alice = apptbook.select(|owner, _| owner.handle == "alice");
+    assert!(!markdown(&alice).contains(" - Alice"));
+}
+
+#[test]
+fn org_entries() {
+    let apptbook = sample_book();
+
+    assert_eq!(
+        org(&apptbook.select(|_, _| true)),
+        "* Alice\n\
+         ** Review *draft*\n   <2026-10-18 Sun 10:00-11:00>\n\
+         ** Retreat\n   <2026-10-19 Mon 09:00>--<2026-10-20 Tue 17:00>\n\
+         * Bob\n\
+         ** Lunch\n   <2026-10-18 Sun 12:00-12:30>\n"
+    );
+}
+
+#[test]
+fn day_agenda() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+    let now = NaiveDate::from_ymd(2026, 10, 18).and_hms(12, 15, 0);
+
+    assert_eq!(
+        day(&hits, NaiveDate::from_ymd(2026, 10, 18), now),
+        "Sunday, 10/18/2026\n\
+         \x20 10:00-11:00  Review *draft* - Alice  (done)\n\
+         \x20 12:00-12:30  Lunch - Bob  (now)\n"
+    );
+    assert_eq!(
+        day(&hits, NaiveDate::from_ymd(2026, 10, 20), now),
+        "Tuesday, 10/20/2026\n  10/19 09:00-17:00  Retreat\n"
+    );
+    assert_eq!(
+        day(&hits, NaiveDate::from_ymd(2026, 10, 21), now),
+        "Wednesday, 10/21/2026\n  No appointments\n"
+    );
+
+    let mut apptbook = sample_book();
+    apptbook.owners[0].appointments[0].status = crate::appointment::Status::Cancelled;
+    assert!(day(
+        &apptbook.select(|_, _| true),
+        NaiveDate::from_ymd(2026, 10, 18),
+        now
+    )
+    .contains("  10:00-11:00  Review *draft* - Alice  (cancelled)\n"));
+}
+
+#[test]
+fn all_day_agenda() {
+    use crate::appointment::Appointment;
+
+    let mut apptbook = crate::book::Book::default();
+    let date = |day| NaiveDate::from_ymd(2026, 10, day);
+    apptbook
+        .add(
+            "Alice",
+            Appointment::new_all_day("Holiday", date(19), date(19)).unwrap(),
+        )
+        .unwrap();
+    apptbook
+        .add(
+            "Alice",
+            Appointment::new_all_day("Vacation", date(20), date(23)).unwrap(),
+        )
+        .unwrap();
+    let hits = apptbook.select(|_, _| true);
+
+    assert_eq!(
+        markdown(&hits),
+        "## Monday, 10/19/2026\n\n\
+         - All day Holiday (1 day)\n\
+         \n\
+         ## Tuesday, 10/20/2026\n\n\
+         - All day to 10/23/2026 Vacation (4 days)\n"
+    );
+    assert_eq!(
+        org(&hits),
+        "* Alice\n\
+         ** Holiday\n   <2026-10-19 Mon>\n\
+         ** Vacation\n   <2026-10-20 Tue>--<2026-10-23 Fri>\n"
+    );
+    assert_eq!(
+        day(&hits, date(23), date(19).and_hms(12, 0, 0)),
+        "Friday, 10/23/2026\n  All day  Vacation\n"
+    );
+    assert_eq!(
+        day(&hits, date(24), date(19).and_hms(12, 0, 0)),
+        "Saturday, 10/24/2026\n  No appointments\n"
+    );
+}
+
+#[test]
+fn next_appointment() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+
+    assert_eq!(
+        next(&hits, NaiveDate::from_ymd(2026, 10, 18).and_hms(12, 0, 0)),
+        "> Retreat (Alice)\n\
+         | 10/19/2026 09:00 to 10/20/2026 17:00\n\
+         Starts in 21 hours\n"
+    );
+    assert_eq!(
+        next(&hits, NaiveDate::from_ymd(2026, 10, 19).and_hms(9, 0, 0)),
+        "No upcoming appointments\n"
+    );
+
+    // Cancelled appointments are skipped
+    let mut apptbook = sample_book();
+    apptbook.owners[0].appointments[1].status = crate::appointment::Status::Cancelled;
+    assert_eq!(
+        next(
+            &apptbook.select(|_, _| true),
+            NaiveDate::from_ymd(2026, 10, 18).and_hms(12, 0, 0)
+        ),
+        "No upcoming appointments\n"
+    );
+
+    assert_eq!(countdown(Duration::seconds(30)), "in less than a minute");
+    assert_eq!(countdown(Duration::minutes(61)), "in 1 hour 1 minute");
+    assert_eq!(
+        countdown(Duration::minutes(3 * 24 * 60 + 150)),
+        "in 3 days 2 hours"
+    );
+}
@@ -0,0 +1,499 @@
+use crate::attendees::{Attendee, Response};
+use chrono::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// date regex received from https://stackoverflow.com/questions/15491894/regex-to-validate-date-format-dd-mm-yyyy
+pub const DATE_PATTERN: &str = r"^(((0[13-9]|1[012])[/](0[1-9]|[12][0-9]|30)|(0[13578]|1[02])[/]31|02[/](0[1-9]|1[0-9]|2[0-8]))[/][0-9]{4}|02[/]29[/]([0-9]{2}(([2468][048]|[02468][48])|[13579][26])|([13579][26]|[02468][048]|0[0-9]|1[0-6])00))$";
+
+// time regex received from https://stackoverflow.com/questions/7536755/regular-expression-for-matching-hhmm-time-format/7536768
+pub const TIME_PATTERN: &str = r"^(0[0-9]|1[0-9]|2[0-3]):[0-5][0-9]$";
+
+// Date and time as entered at the add prompts
+pub const DATE_TIME_FORMAT: &str = "%m/%d/%Y %H:%M";
+
+// Where an appointment is in its lifecycle. A tentative appointment becomes confirmed and then
+// completed, and one that didn't happen ends up cancelled or a no-show
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Hash, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum Status {
+    Tentative,
+    #[default]
+    Confirmed,
+    Completed,
+    Cancelled,
+    NoShow,
+}
+
+impl Status {
+    pub fn parse(input: &str) -> Result<Status, String> {
+        match input.trim().to_lowercase().as_str() {
+            "tentative" => Ok(Status::Tentative),
+            "confirmed" => Ok(Status::Confirmed),
+            "completed" => Ok(Status::Completed),
+            "cancelled" | "canceled" => Ok(Status::Cancelled),
+            "no-show" | "noshow" => Ok(Status::NoShow),
+            _ => Err(format!(
+                "Invalid status. Must be tentative, confirmed, completed, cancelled or no-show: {}",
+                input.trim()
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Status::Tentative => "tentative",
+            Status::Confirmed => "confirmed",
+            Status::Completed => "completed",
+            Status::Cancelled => "cancelled",
+            Status::NoShow => "no-show",
+        }
+    }
+
+    // Tentative appointments can be confirmed or cancelled, and confirmed ones completed,
+    // cancelled or marked as a no-show. Completed, cancelled and no-show are final
+    pub fn can_become(self, next: Status) -> bool {
+        matches!(
+            (self, next),
+            (Status::Tentative, Status::Confirmed)
+                | (Status::Tentative, Status::Cancelled)
+                | (Status::Confirmed, Status::Completed)
+                | (Status::Confirmed, Status::Cancelled)
+                | (Status::Confirmed, Status::NoShow)
+        )
+    }
+
+    pub fn is_confirmed(&self) -> bool {
+        *self == Status::Confirmed
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Appointment {
+    // Unique within the book, and assigned when the appointment is added to it. Zero until then
+    #[serde(default)]
+    pub id: u64,
+    pub description: String,
+    pub start_date_time: NaiveDateTime,
+    pub end_date_time: NaiveDateTime,
+    // iCalendar UID given by a calendar client that created the appointment over CalDAV
+    #[serde(default)]
+    pub uid: Option<String>,
+    // Minutes before the start to send a reminder, smallest first
+    #[serde(default)]
+    pub reminders: Vec<i64>,
+    // Normalized by tags::normalize and kept sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Where the appointment is, such as a room or an address
+    #[serde(default)]
+    pub location: Option<String>,
+    // Longer text such as an agenda or dial-in numbers, which may span several lines
+    #[serde(default)]
+    pub notes: Option<String>,
+    // Other owners taking part, besides the owner the appointment is stored with
+    #[serde(default)]
+    pub attendees: Vec<Attendee>,
+    #[serde(default)]
+    pub status: Status,
+    // Takes up whole days, such as a holiday or vacation. The start is midnight on the first day
+    // and the end midnight after the last, so range queries treat it like any other appointment
+    #[serde(default)]
+    pub all_day: bool,
+}
+
+impl Default for Appointment {
+    fn default() -> Appointment {
+        let epoch = NaiveDate::from_ymd(1970, 1, 1).and_hms(0, 0, 0);
+
+        Appointment {
+            id: 0,
+            description: String::new(),
+            start_date_time: epoch,
+            end_date_time: epoch,
+            uid: None,
+            reminders: Vec::new(),
+            tags: Vec::new(),
+            location: None,
+            notes: None,
+            attendees: Vec::new(),
+            status: Status::default(),
+            all_day: false,
+        }
+    }
+}
+
+impl Appointment {
+    // Build an appointment, rejecting one that ends before it starts like the add prompts do
+    pub fn new(
+        description: &str,
+        start_date_time: NaiveDateTime,
+        end_date_time: NaiveDateTime,
+    ) -> Result<Appointment, String> {
+        if end_date_time < start_date_time {
+            return Err("Invalid time. End time cannot occur before start time".to_string());
+        }
+
+        Ok(Appointment {
+            description: description.trim().to_string(),
+            start_date_time,
+            end_date_time,
+            ..Default::default()
+        })
+    }
+
+    // Build an all-day appointment from its first to its last day
+    pub fn new_all_day(
+        description: &str,
+        first_day: NaiveDate,
+        last_day: NaiveDate,
+    ) -> Result<Appointment, String> {
+        if last_day < first_day {
+            return Err("Invalid date. End date cannot occur before start date".to_string());
+        }
+
+        Ok(Appointment {
+            all_day: true,
+            ..Appointment::new(
+                description,
+                first_day.and_hms(0, 0, 0),
+                last_day.succ().and_hms(0, 0, 0),
+            )?
+        })
+    }
+
+    // An appointment with only a description and times like "10/18/2026 10:00", for tests
+    #[cfg(test)]
+    pub fn sample(description: &str, start: &str, end: &str) -> Appointment {
+        Appointment {
+            description: description.to_string(),
+            start_date_time: NaiveDateTime::parse_from_str(start, DATE_TIME_FORMAT).unwrap(),
+            end_date_time: NaiveDateTime::parse_from_str(end, DATE_TIME_FORMAT).unwrap(),
+            ..Default::default()
+        }
+    }
+
+    // Length of the appointment in minutes
+    pub fn duration_minutes(&self) -> i64 {
+        (self.end_date_time - self.start_date_time).num_minutes()
+    }
+
+    // The last day the appointment occupies. One ending exactly at midnight doesn't spill over
+    // into the next day
+    pub fn last_day(&self) -> NaiveDate {
+        if self.end_date_time > self.start_date_time
+            && self.end_date_time.time() == NaiveTime::from_hms(0, 0, 0)
+        {
+            self.end_date_time.date().pred()
+        } else {
+            self.end_date_time.date()
+        }
+    }
+
+    // Whether the appointment occupies any part of the day
+    pub fn occurs_on(&self, day: NaiveDate) -> bool {
+        self.start_date_time.date() <= day && day <= self.last_day()
+    }
+
+    // Whether the owner with the handle is attending, and hasn't declined
+    pub fn is_attending(&self, handle: &str) -> bool {
+        self.attendees
+            .iter()
+            .any(|attendee| attendee.handle == handle && attendee.response != Response::Declined)
+    }
+
+    // Whether the two appointments are scheduled at the same time. Back-to-back appointments
+    // don't overlap
+    pub fn overlaps(&self, other: &Appointment) -> bool {
+        self.start_date_time < other.end_date_time && other.start_date_time < self.end_date_time
+    }
+
+    // Whether the two appointments overlap and neither is cancelled, which frees its time. All-day
+    // appointments mark days rather than book time, so they don't conflict either
+    pub fn conflicts_with(&self, other: &Appointment) -> bool {
+        !self.is_cancelled()
+            && !other.is_cancelled()
+            && !self.all_day
+            && !other.all_day
+            && self.overlaps(other)
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.status == Status::Cancelled
+    }
+
+    // Move the appointment to the next status in its lifecycle
+    pub fn set_status(&mut self, next: Status) -> Result<(), String> {
+        if self.status == next {
+            return Err(format!("The appointment is already {}", next.name()));
+        }
+
+        if !self.status.can_become(next) {
+            return Err(format!(
+                "A {} appointment cannot become {}",
+                self.status.name(),
+                next.name()
+            ));
+        }
+
+        self.status = next;
+        Ok(())
+    }
+
+    // Format start and end for display. The end date is omitted when the appointment starts and
+    // ends on the same day
+    pub fn formatted_times(&self) -> (String, String) {
+        let formatted_sdt = self.start_date_time.format("%m/%d/%Y %H:%M").to_string();
+
+        let formatted_edt = if self.start_date_time.date() == self.end_date_time.date() {
+            self.end_date_time.format("%H:%M").to_string()
+        } else {
+            self.end_date_time.format("%m/%d/%Y %H:%M").to_string()
+        };
+
+        (formatted_sdt, formatted_edt)
+    }
+
+    // Format when the appointment takes place for display, e.g. "03/11/2020 07:30 to 08:30", or
+    // "All day 03/11/2020 to 03/12/2020" for an all-day appointment
+    pub fn formatted_span(&self) -> String {
+        if !self.all_day {
+            let (formatted_sdt, formatted_edt) = self.formatted_times();
+            return format!("{} to {}", formatted_sdt, formatted_edt);
+        }
+
+        let first_day = self.start_date_time.date();
+
+        if first_day == self.last_day() {
+            format!("All day {}", first_day.format("%m/%d/%Y"))
+        } else {
+            format!(
+                "All day {} to {}",
+                first_day.format("%m/%d/%Y"),
+                self.last_day().format("%m/%d/%Y")
+            )
+        }
+    }
+
+    // Format the length for display, in days for an all-day appointment
+    pub fn formatted_duration(&self) -> String {
+        if !self.all_day {
+            return format!("{} minutes", self.duration_minutes());
+        }
+
+        match (self.last_day() - self.start_date_time.date()).num_days() + 1 {
+            1 => "1 day".to_string(),
+            days => format!("{} days", days),
+        }
+    }
+}
+
+// Parse a mm/dd/yyyy date, as accepted by the add prompts
+pub fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    let date_re = Regex::new(DATE_PATTERN).unwrap();
+
+    if date_re.is_match(input.trim()) {
+        Ok(NaiveDate::parse_from_str(input.trim(), "%m/%d/%Y").unwrap())
+    } else {
+        Err("Invalid date. Required format: mm/dd/yyyy".to_string())
+    }
+}
+
+// Parse a date and time in the given chrono format. The add prompts' format is checked against the
+// date and time regexes, giving the same error messages
+pub fn parse_date_time(input: &str, format: &str) -> Result<NaiveDateTime, String> {
+    if format != DATE_TIME_FORMAT {
+        return NaiveDateTime::parse_from_str(input.trim(), format)
+            .map_err(|_| format!("Invalid date/time. Required format: {}", format));
+    }
+
+    let (date, time) = match input.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [date, time] => (parse_date(date)?, *time),
+        _ => return Err("Invalid date/time. Required format: mm/dd/yyyy hh:mm".to_string()),
+    };
+
+    let time_re = Regex::new(TIME_PATTERN).unwrap();
+
+    if !time_re.is_match(time) {
+        return Err("Invalid time. Required (24-hour clock) format: hh:mm".to_string());
+    }
+
+    Ok(date.and_time(NaiveTime::parse_from_str(time, "%H:%M").unwrap()))
+}
+
+// Sort appointments by start_date_time. If start_date_times are equal, sort by end_date_time. If
+// end_date_times are equal, sort by description (alphabetically)
+pub fn sort(appts: &mut [Appointment]) {
+    appts.sort_by(|a, b| {
+        a.start_date_time
+            .cmp(&b.start_date_time)
+            .then(a.end_date_time.cmp(&b.end_date_time))
+            .then(a.description.cmp(&b.description))
+    });
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn formatted_times_same_day() {
+    let appt = Appointment {
+        description: "Dentist appointment".to_string(),
+        start_date_time: NaiveDateTime::parse_from_str("03/11/2020 07:30", "%m/%d/%Y %H:%M")
+            .unwrap(),
+        end_date_time: NaiveDateTime::parse_from_str("03/11/2020 08:30", "%m/%d/%Y %H:%M").unwrap(),
+        ..Default::default()
+    };
+
+    let (sdt, edt) = appt.formatted_times();
+
+    assert_eq!(sdt, "03/11/2020 07:30");
+    assert_eq!(edt, "08:30");
+    assert_eq!(appt.duration_minutes(), 60);
+}
+
+#[test]
+fn parse_date_requires_format() {
+    assert_eq!(
+        parse_date("02/29/2020"),
+        Ok(NaiveDate::from_ymd(2020, 2, 29))
+    );
+    assert!(parse_date("02/29/2019").is_err());
+    assert!(parse_date("2/2/2020").is_err());
+}
+
+#[test]
+fn parse_date_time_validates_like_prompts() {
+    assert_eq!(
+        parse_date_time("02/29/2020 23:59", DATE_TIME_FORMAT),
+        Ok(NaiveDate::from_ymd(2020, 2, 29).and_hms(23, 59, 0))
+    );
+    assert!(parse_date_time("02/29/2019 10:00", DATE_TIME_FORMAT).is_err());
+    assert!(parse_date_time("02/28/2019 24:00", DATE_TIME_FORMAT).is_err());
+    assert!(parse_date_time("02/28/2019", DATE_TIME_FORMAT).is_err());
+    assert_eq!(
+        parse_date_time("2020-02-29T08:05", "%Y-%m-%dT%H:%M"),
+        Ok(NaiveDate::from_ymd(2020, 2, 29).and_hms(8, 5, 0))
+    );
+}
+
+#[test]
+fn new_rejects_end_before_start() {
+    let start = NaiveDate::from_ymd(2020, 2, 29).and_hms(10, 0, 0);
+    let end = NaiveDate::from_ymd(2020, 2, 29).and_hms(9, 0, 0);
+
+    assert!(Appointment::new("Backwards", start, end).is_err());
+    assert_eq!(
+        Appointment::new(" Forwards ", end, start)
+            .unwrap()
+            .description,
+        "Forwards"
+    );
+}
+
+#[test]
+fn last_day_ignores_midnight_end() {
+    let appt = Appointment {
+        description: "Overnight".to_string(),
+        start_date_time: NaiveDate::from_ymd(2020, 3, 11).and_hms(22, 0, 0),
+        end_date_time: NaiveDate::from_ymd(2020, 3, 13).and_hms(0, 0, 0),
+        ..Default::default()
+    };
+
+    assert_eq!(appt.last_day(), NaiveDate::from_ymd(2020, 3, 12));
+    assert!(appt.occurs_on(NaiveDate::from_ymd(2020, 3, 11)));
+    assert!(appt.occurs_on(NaiveDate::from_ymd(2020, 3, 12)));
+    assert!(!appt.occurs_on(NaiveDate::from_ymd(2020, 3, 13)));
+}
+
+#[test]
+fn overlapping_appointments() {
+    let at = |hour| NaiveDate::from_ymd(2020, 3, 11).and_hms(hour, 0, 0);
+    let appt = |start, end| Appointment::new("", at(start), at(end)).unwrap();
+
+    assert!(appt(9, 11).overlaps(&appt(10, 12)));
+    assert!(appt(9, 12).overlaps(&appt(10, 11)));
+    assert!(!appt(9, 10).overlaps(&appt(10, 11)));
+    assert!(!appt(11, 12).overlaps(&appt(9, 10)));
+
+    let mut cancelled = appt(10, 12);
+    cancelled.status = Status::Cancelled;
+    assert!(appt(9, 11).conflicts_with(&appt(10, 12)));
+    assert!(!appt(9, 11).conflicts_with(&cancelled));
+}
+
+#[test]
+fn status_lifecycle() {
+    let mut appt = Appointment {
+        status: Status::Tentative,
+        ..Default::default()
+    };
+
+    assert!(appt.set_status(Status::Completed).is_err());
+    assert!(appt.set_status(Status::Confirmed).is_ok());
+    assert_eq!(
+        appt.set_status(Status::Confirmed),
+        Err("The appointment is already confirmed".to_string())
+    );
+    assert!(appt.set_status(Status::NoShow).is_ok());
+    assert_eq!(
+        appt.set_status(Status::Confirmed),
+        Err("A no-show appointment cannot become confirmed".to_string())
+    );
+
+    assert_eq!(Status::parse(" Canceled").unwrap(), Status::Cancelled);
+    assert!(Status::parse("maybe").is_err());
+    assert_eq!(
+        serde_json::to_string(&Status::NoShow).unwrap(),
+        "\"no-show\""
+    );
+}
+
+#[test]
+fn formatted_times_multi_day() {
+    let appt = Appointment {
+        description: "Conference".to_string(),
+        start_date_time: NaiveDateTime::parse_from_str("03/11/2020 07:30", "%m/%d/%Y %H:%M")
+            .unwrap(),
+        end_date_time: NaiveDateTime::parse_from_str("03/12/2020 08:30", "%m/%d/%Y %H:%M").unwrap(),
+        ..Default::default()
+    };
+
+    let (sdt, edt) = appt.formatted_times();
+
+    assert_eq!(sdt, "03/11/2020 07:30");
+    assert_eq!(edt, "03/12/2020 08:30");
+    assert_eq!(
+        appt.formatted_span(),
+        "03/11/2020 07:30 to 03/12/2020 08:30"
+    );
+}
+
+#[test]
+fn all_day_appointments() {
+    let day = |day| NaiveDate::from_ymd(2020, 3, day);
+    let holiday = Appointment::new_all_day("Holiday", day(11), day(11)).unwrap();
+    let vacation = Appointment::new_all_day("Vacation", day(12), day(14)).unwrap();
+
+    assert_eq!(holiday.end_date_time, day(12).and_hms(0, 0, 0));
+    assert_eq!(holiday.last_day(), day(11));
+    assert!(!holiday.occurs_on(day(12)));
+    assert_eq!(holiday.formatted_span(), "All day 03/11/2020");
+    assert_eq!(holiday.formatted_duration(), "1 day");
+    assert_eq!(
+        vacation.formatted_span(),
+        "All day 03/12/2020 to 03/14/2020"
+    );
+    assert_eq!(vacation.formatted_duration(), "3 days");
+    assert!(Appointment::new_all_day("Backwards", day(12), day(11)).is_err());
+
+    // All-day appointments don't conflict with appointments on the same day
+    let meeting = Appointment::new(
+        "Meeting",
+        day(13).and_hms(9, 0, 0),
+        day(13).and_hms(10, 0, 0),
+    )
+    .unwrap();
+    assert!(vacation.overlaps(&meeting));
+    assert!(!vacation.conflicts_with(&meeting));
+}
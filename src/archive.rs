@@ -0,0 +1,114 @@
+use crate::book::Book;
+use crate::config::Retention;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::path::Path;
+
+pub const ARCHIVE_FILE: &str = "apptbook_archive.txt";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Outcome {
+    pub archived: usize,
+    pub purged: usize,
+}
+
+// Read the archive file. Unlike the book, a missing archive isn't created until something is
+// archived
+pub fn load(path: &str) -> Book {
+    if Path::new(path).exists() {
+        Book::load(path)
+    } else {
+        Book::default()
+    }
+}
+
+// Apply the retention policy as of now: appointments that ended before the archive cutoff move
+// from the book into the archive, and those that ended before the purge cutoff are deleted
+pub fn apply(
+    retention: &Retention,
+    apptbook: &mut Book,
+    archive: &mut Book,
+    now: NaiveDateTime,
+) -> Outcome {
+    let mut outcome = Outcome::default();
+
+    // A cutoff earlier than any representable time leaves everything where it is
+    let cutoff = |days| now.checked_sub_signed(Duration::days(days));
+
+    if let Some(cutoff) = retention.purge_after_days.and_then(cutoff) {
+        outcome.purged += apptbook.remove_where(|_, appt| appt.end_date_time < cutoff);
+        outcome.purged += archive.remove_where(|_, appt| appt.end_date_time < cutoff);
+    }
+
+    if let Some(cutoff) = retention.archive_after_days.and_then(cutoff) {
+        for owner in &mut apptbook.owners {
+            let appts = std::mem::take(&mut owner.appointments);
+            let (past, upcoming): (Vec<_>, Vec<_>) = appts
+                .into_iter()
+                .partition(|appt| appt.end_date_time < cutoff);
+
+            owner.appointments = upcoming;
+
+            for appt in past {
+                archive.add_for(owner, appt);
+                outcome.archived += 1;
+            }
+        }
+    }
+
+    outcome
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn apply_archives_and_purges() {
+    let now = NaiveDateTime::parse_from_str("03/31/2020 12:00", "%m/%d/%Y %H:%M").unwrap();
+
+    let mut apptbook = Book::sample(&[
+        ("Tom", "Ancient", "01/01/2020 09:00", "01/01/2020 10:00"),
+        ("Tom", "Old", "03/01/2020 09:00", "03/01/2020 10:00"),
+        ("Tom", "Recent", "03/30/2020 09:00", "03/30/2020 10:00"),
+    ]);
+    apptbook.find_mut("tom").unwrap().email = Some("tom@example.com".to_string());
+
+    let mut archive = Book::sample(&[("Ann", "Archived", "01/02/2020 09:00", "01/02/2020 10:00")]);
+
+    let retention = Retention {
+        archive_after_days: Some(7),
+        purge_after_days: Some(60),
+    };
+
+    let outcome = apply(&retention, &mut apptbook, &mut archive, now);
+
+    assert_eq!(
+        outcome,
+        Outcome {
+            archived: 1,
+            purged: 2
+        }
+    );
+
+    let tom = apptbook.find("tom").unwrap();
+    assert_eq!(tom.appointments.len(), 1);
+    assert_eq!(tom.appointments[0].description, "Recent");
+
+    let archived = archive.find("tom").unwrap();
+    assert_eq!(archived.appointments.len(), 1);
+    assert_eq!(archived.appointments[0].description, "Old");
+    assert_eq!(archived.email, Some("tom@example.com".to_string()));
+}
+
+#[test]
+fn apply_without_policy_keeps_everything() {
+    let now = NaiveDateTime::parse_from_str("03/31/2020 12:00", "%m/%d/%Y %H:%M").unwrap();
+
+    let mut apptbook = Book::sample(&[("Tom", "Ancient", "01/01/2020 09:00", "01/01/2020 10:00")]);
+    let mut archive = Book::default();
+
+    let outcome = apply(&Retention::default(), &mut apptbook, &mut archive, now);
+
+    assert_eq!(outcome, Outcome::default());
+    assert!(archive.is_empty());
+}
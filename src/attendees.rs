@@ -0,0 +1,117 @@
+use crate::appointment::Appointment;
+use crate::book::Book;
+use serde::{Deserialize, Serialize};
+
+// An attendee's answer to an appointment. Invitations start out tentative until answered
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Hash, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Response {
+    Accepted,
+    Declined,
+    #[default]
+    Tentative,
+}
+
+impl Response {
+    pub fn parse(input: &str) -> Result<Response, String> {
+        match input.trim().to_lowercase().as_str() {
+            "accepted" | "accept" | "yes" => Ok(Response::Accepted),
+            "declined" | "decline" | "no" => Ok(Response::Declined),
+            "tentative" | "maybe" => Ok(Response::Tentative),
+            _ => Err(format!(
+                "Invalid response. Must be accepted, declined or tentative: {}",
+                input.trim()
+            )),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Response::Accepted => "accepted",
+            Response::Declined => "declined",
+            Response::Tentative => "tentative",
+        }
+    }
+}
+
+// Another owner taking part in an appointment, which is stored with the owner who organized it
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Hash)]
+pub struct Attendee {
+    // The attendee's owner handle. Names are accepted when adding an appointment, and turned into
+    // handles by Book::add
+    pub handle: String,
+    #[serde(default)]
+    pub response: Response,
+}
+
+impl Attendee {
+    pub fn new(name: &str) -> Attendee {
+        Attendee {
+            handle: name.trim().to_string(),
+            response: Response::default(),
+        }
+    }
+}
+
+// Parse comma-separated owner names or handles into attendees. "none" or an empty string means
+// no attendees
+pub fn parse(input: &str) -> Vec<Attendee> {
+    if input.trim().eq_ignore_ascii_case("none") {
+        return Vec::new();
+    }
+
+    input
+        .split(',')
+        .filter(|name| !name.trim().is_empty())
+        .map(Attendee::new)
+        .collect()
+}
+
+// The attendees with their responses for display, e.g. "Bob (accepted), Carol (tentative)"
+pub fn describe(apptbook: &Book, appt: &Appointment) -> String {
+    let attendees: Vec<String> = appt
+        .attendees
+        .iter()
+        .map(|attendee| {
+            let name = apptbook
+                .find(&attendee.handle)
+                .map_or(attendee.handle.as_str(), |owner| owner.name.as_str());
+
+            format!("{} ({})", name, attendee.response.name())
+        })
+        .collect();
+
+    attendees.join(", ")
+}
+
+// Print the organizer of an appointment viewed as one of its attendees, and the attendees, in the
+// style of the detailed appointment listings
+pub fn print(apptbook: &Book, viewer: &str, appt: &Appointment) {
+    if appt.attendees.is_empty() {
+        return;
+    }
+
+    if let Some(organizer) = apptbook.find_id(appt.id).map(|hit| hit.owner) {
+        if organizer.handle != viewer {
+            println!("| Organizer: {}", organizer.name);
+        }
+    }
+
+    println!("| Attendees: {}", describe(apptbook, appt));
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn parse_attendees_and_responses() {
+    let attendees = parse(" Bob Smith, ,carol");
+    assert_eq!(attendees.len(), 2);
+    assert_eq!(attendees[0].handle, "Bob Smith");
+    assert_eq!(attendees[1].response, Response::Tentative);
+    assert!(parse("none").is_empty());
+
+    assert_eq!(Response::parse(" Accepted").unwrap(), Response::Accepted);
+    assert_eq!(Response::parse("no").unwrap(), Response::Declined);
+    assert!(Response::parse("later").is_err());
+}
@@ -0,0 +1,688 @@
+use crate::appointment::{self, Appointment};
+use crate::owner::{self, Owner};
+use crate::templates::Template;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::fs::OpenOptions;
+use std::io::Read;
+
+pub const STORAGE_FILE: &str = "apptbook.txt";
+
+// Version 1 was a bare map of owner name to appointments, and carried no version field
+const STORAGE_VERSION: u32 = 2;
+
+// An appointment along with the owner it belongs to
+#[derive(Clone, Copy)]
+pub struct Hit<'a> {
+    pub owner: &'a Owner,
+    pub appt: &'a Appointment,
+    // The owner the appointment is stored with. The same as owner unless the hit is for one of
+    // the appointment's attendees
+    pub organizer: &'a Owner,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Book {
+    version: u32,
+    // The most recently assigned appointment ID
+    #[serde(default)]
+    last_id: u64,
+    pub owners: Vec<Owner>,
+    // Kinds of appointments entered often, sorted by name
+    #[serde(default)]
+    pub templates: Vec<Template>,
+}
+
+impl Default for Book {
+    fn default() -> Book {
+        Book {
+            version: STORAGE_VERSION,
+            last_id: 0,
+            owners: Vec::new(),
+            templates: Vec::new(),
+        }
+    }
+}
+
+impl Book {
+    // A book of sample appointments given as (owner, description, start, end), for tests
+    #[cfg(test)]
+    pub fn sample(appts: &[(&str, &str, &str, &str)]) -> Book {
+        let mut apptbook = Book::default();
+
+        for (owner, description, start, end) in appts {
+            apptbook
+                .add(owner, Appointment::sample(description, start, end))
+                .unwrap();
+        }
+
+        apptbook
+    }
+
+    // Read the book from the storage file. If the file doesn't exist, create one
+    pub fn load(path: &str) -> Book {
+        // String to hold read-in book from storage file
+        let mut contents = String::new();
+
+        match OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)
+        {
+            Ok(ref mut file) => {
+                file.read_to_string(&mut contents)
+                    .expect("Failed to read file");
+            }
+            Err(err) => {
+                panic!("Failed to open file: {}", err);
+            }
+        }
+
+        Book::from_json(&contents).expect("Failed to parse storage file")
+    }
+
+    pub fn save(&self, path: &str) {
+        let serialized = serde_json::to_string(self).unwrap();
+
+        fs::write(path, serialized).expect("Failed to write file");
+    }
+
+    // Parse a stored book, upgrading the version 1 format (a map of owner name to appointments)
+    // if necessary. An empty string is an empty book
+    pub fn from_json(contents: &str) -> serde_json::Result<Book> {
+        if contents.trim().is_empty() {
+            return Ok(Book::default());
+        }
+
+        let value: serde_json::Value = serde_json::from_str(contents)?;
+
+        if value.get("version").is_some() {
+            let mut book: Book = serde_json::from_value(value)?;
+            book.assign_missing_ids();
+            return Ok(book);
+        }
+
+        let legacy: HashMap<String, Vec<Appointment>> = serde_json::from_value(value)?;
+        let mut book = Book::default();
+
+        // Add owners in name order so appointment IDs are assigned predictably
+        let mut names: Vec<&String> = legacy.keys().collect();
+        names.sort();
+
+        for name in names {
+            for appt in &legacy[name] {
+                book.add(name, appt.clone())
+                    .map_err(serde::de::Error::custom)?;
+            }
+        }
+
+        Ok(book)
+    }
+
+    // Give an ID to any appointment stored without one, such as those written by older versions
+    fn assign_missing_ids(&mut self) {
+        let max_id = self
+            .owners
+            .iter()
+            .flat_map(|owner| owner.appointments.iter())
+            .map(|appt| appt.id)
+            .max()
+            .unwrap_or(0);
+        self.last_id = self.last_id.max(max_id);
+
+        for owner in &mut self.owners {
+            for appt in &mut owner.appointments {
+                if appt.id == 0 {
+                    self.last_id += 1;
+                    appt.id = self.last_id;
+                }
+            }
+        }
+    }
+
+    // Give a new appointment the next ID, or make sure one with an ID won't have it reused
+    fn claim_id(&mut self, appt: &mut Appointment) {
+        if appt.id == 0 {
+            self.last_id += 1;
+            appt.id = self.last_id;
+        } else {
+            self.last_id = self.last_id.max(appt.id);
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.owners.is_empty()
+    }
+
+    // Collect every appointment matching the predicate, ordered by owner then start time
+    pub fn select<F>(&self, mut predicate: F) -> Vec<Hit<'_>>
+    where
+        F: FnMut(&Owner, &Appointment) -> bool,
+    {
+        let mut hits = Vec::new();
+
+        for owner in &self.owners {
+            for appt in &owner.appointments {
+                if predicate(owner, appt) {
+                    hits.push(Hit {
+                        owner,
+                        appt,
+                        organizer: owner,
+                    });
+                }
+            }
+        }
+
+        hits
+    }
+
+    // Remove every appointment matching the predicate. Returns the number removed
+    pub fn remove_where<F>(&mut self, mut predicate: F) -> usize
+    where
+        F: FnMut(&Owner, &Appointment) -> bool,
+    {
+        let mut removed = 0;
+
+        for owner in &mut self.owners {
+            let appts = std::mem::take(&mut owner.appointments);
+            let (matched, kept): (Vec<_>, Vec<_>) =
+                appts.into_iter().partition(|appt| predicate(owner, appt));
+
+            removed += matched.len();
+            owner.appointments = kept;
+        }
+
+        removed
+    }
+
+    // Like select, but an appointment is also visited for each of its attendees that hasn't
+    // declined, as one of theirs. Use this for an owner's schedule, and select to visit every
+    // appointment once
+    pub fn select_participating<F>(&self, mut predicate: F) -> Vec<Hit<'_>>
+    where
+        F: FnMut(&Owner, &Appointment) -> bool,
+    {
+        let mut hits = Vec::new();
+
+        for owner in &self.owners {
+            for hit in self.participating(owner) {
+                if predicate(owner, hit.appt) {
+                    hits.push(hit);
+                }
+            }
+        }
+
+        hits
+    }
+
+    // The appointments an owner takes part in: their own, and those of others they're attending.
+    // Ordered by start time
+    pub fn attending(&self, handle: &str) -> Vec<&Appointment> {
+        match self.owners.iter().find(|owner| owner.handle == handle) {
+            Some(owner) => self
+                .participating(owner)
+                .into_iter()
+                .map(|hit| hit.appt)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn participating<'a>(&'a self, owner: &'a Owner) -> Vec<Hit<'a>> {
+        let mut hits: Vec<Hit> = self
+            .owners
+            .iter()
+            .flat_map(|organizer| {
+                organizer
+                    .appointments
+                    .iter()
+                    .filter(move |appt| {
+                        organizer.handle == owner.handle || appt.is_attending(&owner.handle)
+                    })
+                    .map(move |appt| Hit {
+                        owner,
+                        appt,
+                        organizer,
+                    })
+            })
+            .collect();
+
+        hits.sort_by_key(|hit| (hit.appt.start_date_time, hit.appt.end_date_time));
+        hits
+    }
+
+    pub fn find_id(&self, id: u64) -> Option<Hit<'_>> {
+        self.select(|_, appt| appt.id == id).pop()
+    }
+
+    // Remove the appointment with the ID. Returns its owner's handle along with it
+    pub fn remove_id(&mut self, id: u64) -> Option<(String, Appointment)> {
+        for owner in &mut self.owners {
+            if let Some(index) = owner.appointments.iter().position(|appt| appt.id == id) {
+                return Some((owner.handle.clone(), owner.appointments.remove(index)));
+            }
+        }
+
+        None
+    }
+
+    pub fn find(&self, input: &str) -> Option<&Owner> {
+        self.owners.iter().find(|owner| owner.matches(input))
+    }
+
+    pub fn find_mut(&mut self, input: &str) -> Option<&mut Owner> {
+        self.owners.iter_mut().find(|owner| owner.matches(input))
+    }
+
+    // Push an Appointment to the owner's Vec, keeping it sorted. If the owner doesn't already
+    // exist within the book, create them first, and the same for attendees, who may be given by
+    // name. Returns the appointment's ID
+    pub fn add(&mut self, name: &str, mut appt: Appointment) -> Result<u64, String> {
+        check_owners(name, &appt)?;
+        self.claim_id(&mut appt);
+        let id = appt.id;
+
+        let handle = self.owner_handle(name);
+
+        for attendee in &mut appt.attendees {
+            attendee.handle = self.owner_handle(&attendee.handle);
+        }
+
+        tidy_attendees(&handle, &mut appt);
+
+        let index = self
+            .owners
+            .iter()
+            .position(|owner| owner.handle == handle)
+            .unwrap();
+
+        let appts = &mut self.owners[index].appointments;
+        appts.push(appt);
+        appointment::sort(appts);
+
+        Ok(id)
+    }
+
+    // Push an Appointment for an owner from another book (such as the archive), copying the
+    // owner's record over if this book doesn't have them yet
+    pub fn add_for(&mut self, owner: &Owner, mut appt: Appointment) {
+        self.claim_id(&mut appt);
+
+        let index = match self
+            .owners
+            .iter()
+            .position(|other| other.handle == owner.handle)
+        {
+            Some(index) => index,
+            None => self.insert_owner(Owner {
+                appointments: Vec::new(),
+                ..owner.clone()
+            }),
+        };
+
+        let appts = &mut self.owners[index].appointments;
+        appts.push(appt);
+        appointment::sort(appts);
+    }
+
+    // The handle of the owner with the name or handle, creating them if they don't exist
+    fn owner_handle(&mut self, name: &str) -> String {
+        let index = match self.owners.iter().position(|owner| owner.matches(name)) {
+            Some(index) => index,
+            None => self.insert_owner(Owner::new(name)),
+        };
+
+        self.owners[index].handle.clone()
+    }
+
+    // Insert a new owner, keeping owners ordered by handle. Returns the owner's index
+    fn insert_owner(&mut self, mut owner: Owner) -> usize {
+        // Handles must be unique, so suffix a number if another owner already has this one
+        let base = owner.handle.clone();
+        let mut suffix = 2;
+        while self.owners.iter().any(|other| other.handle == owner.handle) {
+            owner.handle = format!("{}-{}", base, suffix);
+            suffix += 1;
+        }
+
+        let index = self
+            .owners
+            .iter()
+            .position(|other| other.handle > owner.handle)
+            .unwrap_or(self.owners.len());
+        self.owners.insert(index, owner);
+
+        index
+    }
+
+    // Remove an owner with their appointments, and take them off others' appointments they were
+    // attending
+    pub fn remove_owner(&mut self, input: &str) -> Option<Owner> {
+        let index = self.owners.iter().position(|owner| owner.matches(input))?;
+        let removed = self.owners.remove(index);
+
+        for owner in &mut self.owners {
+            for appt in &mut owner.appointments {
+                appt.attendees
+                    .retain(|attendee| attendee.handle != removed.handle);
+            }
+        }
+
+        Some(removed)
+    }
+
+    // Change an owner's display name. Their handle and appointments are kept
+    pub fn rename(&mut self, input: &str, new_name: &str) -> Result<(), String> {
+        if owner::normalize(new_name).is_empty() {
+            return Err("New name cannot be empty".to_string());
+        }
+
+        let index = match self.owners.iter().position(|owner| owner.matches(input)) {
+            Some(index) => index,
+            None => return Err(format!("No owner named {}", input.trim())),
+        };
+
+        if let Some(other) = self.find(new_name) {
+            if other.handle != self.owners[index].handle {
+                return Err(format!(
+                    "{} already exists. Merge the owners instead",
+                    other.name
+                ));
+            }
+        }
+
+        self.owners[index].name = Owner::new(new_name).name;
+
+        Ok(())
+    }
+
+    // Move all of one owner's appointments into another's, then remove the first owner. Contact
+    // info and preferences the target is missing are taken from the merged owner
+    pub fn merge(&mut self, from: &str, into: &str) -> Result<(), String> {
+        let from_index = match self.owners.iter().position(|owner| owner.matches(from)) {
+            Some(index) => index,
+            None => return Err(format!("No owner named {}", from.trim())),
+        };
+        let into_index = match self.owners.iter().position(|owner| owner.matches(into)) {
+            Some(index) => index,
+            None => return Err(format!("No owner named {}", into.trim())),
+        };
+
+        if from_index == into_index {
+            return Err("Cannot merge an owner into themself".to_string());
+        }
+
+        let source = self.owners.remove(from_index);
+        let target = self
+            .owners
+            .iter_mut()
+            .find(|owner| owner.matches(into))
+            .unwrap();
+
+        target.email = target.email.take().or(source.email);
+        target.phone = target.phone.take().or(source.phone);
+        target.preferences.color = target.preferences.color.take().or(source.preferences.color);
+        target.preferences.time_zone = target
+            .preferences
+            .time_zone
+            .take()
+            .or(source.preferences.time_zone);
+
+        target.appointments.extend(source.appointments);
+        appointment::sort(&mut target.appointments);
+
+        // Appointments the merged owner was attending are now the target's
+        let into_handle = target.handle.clone();
+
+        for owner in &mut self.owners {
+            for appt in &mut owner.appointments {
+                for attendee in &mut appt.attendees {
+                    if attendee.handle == source.handle {
+                        attendee.handle = into_handle.clone();
+                    }
+                }
+
+                tidy_attendees(&owner.handle, appt);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+// Drop the organizer from an appointment's attendees, and attendees listed more than once
+fn tidy_attendees(organizer: &str, appt: &mut Appointment) {
+    let mut seen = Vec::new();
+
+    appt.attendees.retain(|attendee| {
+        if attendee.handle == organizer || seen.contains(&attendee.handle) {
+            false
+        } else {
+            seen.push(attendee.handle.clone());
+            true
+        }
+    });
+}
+
+// Check that the owner and each attendee of an appointment to be added have a name, since adding
+// it creates any of them that don't exist yet
+pub fn check_owners(name: &str, appt: &Appointment) -> Result<(), String> {
+    owner::check_name(name)?;
+
+    for attendee in &appt.attendees {
+        owner::check_name(&attendee.handle)?;
+    }
+
+    Ok(())
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn add_matches_existing_owner() {
+    let book = Book::sample(&[
+        ("Alice", "Lunch", "02/02/2020 11:30", "02/02/2020 12:15"),
+        (
+            "alice ",
+            "Breakfast",
+            "02/02/2020 08:00",
+            "02/02/2020 08:30",
+        ),
+    ]);
+
+    assert_eq!(book.owners.len(), 1);
+    assert_eq!(book.owners[0].name, "Alice");
+
+    let alice = book.find("ALICE").unwrap();
+    assert_eq!(alice.appointments[0].description, "Breakfast");
+    assert_eq!(alice.appointments[1].description, "Lunch");
+}
+
+#[test]
+fn add_rejects_empty_owner_names() {
+    use crate::attendees::Attendee;
+
+    let mut book = Book::default();
+
+    assert!(book
+        .add(
+            "",
+            Appointment::sample("Lunch", "02/02/2020 11:30", "02/02/2020 12:15")
+        )
+        .is_err());
+    assert!(book
+        .add(
+            " \t",
+            Appointment::sample("Lunch", "02/02/2020 11:30", "02/02/2020 12:15")
+        )
+        .is_err());
+
+    let mut meeting = Appointment::sample("Meeting", "02/02/2020 09:00", "02/02/2020 10:00");
+    meeting.attendees.push(Attendee::new(" "));
+    assert!(book.add("Alice", meeting).is_err());
+
+    assert!(book.is_empty());
+}
+
+#[test]
+fn ids_are_unique_and_kept() {
+    let mut book = Book::sample(&[
+        ("Tom", "Lunch", "02/02/2020 11:30", "02/02/2020 12:15"),
+        ("Tom", "Nap", "02/02/2020 13:00", "02/02/2020 14:00"),
+    ]);
+
+    let (handle, nap) = book.remove_id(2).unwrap();
+    assert_eq!(handle, "tom");
+    assert_eq!(nap.description, "Nap");
+    assert!(book.remove_id(2).is_none());
+
+    // IDs of removed appointments aren't reused, and re-adding keeps the ID
+    book.add(
+        "Erik",
+        Appointment::sample("Dinner", "02/02/2020 18:00", "02/02/2020 19:00"),
+    )
+    .unwrap();
+    assert_eq!(book.find("erik").unwrap().appointments[0].id, 3);
+    book.add("Erik", nap).unwrap();
+    assert_eq!(book.find("erik").unwrap().appointments[0].id, 2);
+
+    let reloaded = Book::from_json(&serde_json::to_string(&book).unwrap()).unwrap();
+    assert_eq!(reloaded.find("tom").unwrap().appointments[0].id, 1);
+}
+
+#[test]
+fn load_legacy_format() {
+    let legacy = r#"{"Tom":[{"description":"Have lunch with Lisa","start_date_time":"2020-02-02T11:30:00","end_date_time":"2020-02-02T12:15:00"}],"tom ":[{"description":"Nap","start_date_time":"2020-02-01T13:00:00","end_date_time":"2020-02-01T14:00:00"}]}"#;
+
+    let book = Book::from_json(legacy).unwrap();
+
+    assert_eq!(book.owners.len(), 1);
+    assert_eq!(book.owners[0].handle, "tom");
+    assert_eq!(book.owners[0].appointments.len(), 2);
+    assert_eq!(book.owners[0].appointments[0].description, "Nap");
+    assert_eq!(book.owners[0].appointments[0].id, 2);
+}
+
+#[test]
+fn serialize_deserialize_book() {
+    let mut book = Book::sample(&[("Tom", "Lunch", "02/02/2020 11:30", "02/02/2020 12:15")]);
+    book.find_mut("tom").unwrap().email = Some("tom@example.com".to_string());
+
+    let deserialized = Book::from_json(&serde_json::to_string(&book).unwrap()).unwrap();
+    let tom = deserialized.find("Tom").unwrap();
+
+    assert_eq!(tom.email, Some("tom@example.com".to_string()));
+    assert_eq!(tom.appointments[0].description, "Lunch");
+    assert!(Book::from_json("").unwrap().is_empty());
+}
+
+#[test]
+fn remove_where_across_owners() {
+    let mut book = Book::sample(&[
+        ("Bob", "Lunch", "02/02/2020 11:30", "02/02/2020 12:15"),
+        ("Bob", "Nap", "02/02/2020 13:00", "02/02/2020 14:00"),
+        ("Erik", "Lunch", "02/03/2020 11:30", "02/03/2020 12:15"),
+    ]);
+
+    let removed = book.remove_where(|_, appt| appt.description == "Lunch");
+
+    assert_eq!(removed, 2);
+    assert_eq!(book.find("bob").unwrap().appointments.len(), 1);
+    assert!(book.find("erik").unwrap().appointments.is_empty());
+}
+
+#[test]
+fn rename_owner_keeps_appointments() {
+    let mut book = Book::sample(&[
+        ("Bob", "Lunch", "02/02/2020 11:30", "02/02/2020 12:15"),
+        ("Erik", "Dinner", "02/02/2020 18:00", "02/02/2020 19:00"),
+    ]);
+
+    assert!(book.rename("bob", "Robert").is_ok());
+    assert!(book.rename("robert", "ERIK").is_err());
+
+    let robert = book.find("Robert").unwrap();
+    assert_eq!(robert.handle, "bob");
+    assert_eq!(robert.appointments.len(), 1);
+    assert!(book.find("bob").is_some());
+}
+
+#[test]
+fn merge_owners() {
+    let mut book = Book::sample(&[
+        ("Bob", "Lunch", "02/02/2020 11:30", "02/02/2020 12:15"),
+        (
+            "Robert",
+            "Breakfast",
+            "02/02/2020 08:00",
+            "02/02/2020 08:30",
+        ),
+    ]);
+    book.find_mut("bob").unwrap().email = Some("bob@example.com".to_string());
+
+    assert!(book.merge("bob", "bob").is_err());
+    assert!(book.merge("bob", "robert").is_ok());
+
+    assert_eq!(book.owners.len(), 1);
+    let robert = book.find("robert").unwrap();
+    assert_eq!(robert.email, Some("bob@example.com".to_string()));
+    assert_eq!(robert.appointments[0].description, "Breakfast");
+    assert_eq!(robert.appointments[1].description, "Lunch");
+}
+
+#[test]
+fn attendees_share_appointments() {
+    use crate::attendees::{self, Response};
+
+    let mut book = Book::sample(&[("Carol", "Gym", "02/02/2020 07:00", "02/02/2020 08:00")]);
+
+    let mut meeting = Appointment::sample("Planning", "02/02/2020 10:00", "02/02/2020 11:00");
+    meeting.attendees = attendees::parse("Bob Smith, carol, alice, Carol");
+    let id = book.add("Alice", meeting).unwrap();
+
+    // Stored once, with unknown attendees created and the organizer left out
+    let hit = book.find_id(id).unwrap();
+    assert_eq!(hit.owner.handle, "alice");
+    let handles: Vec<&str> = hit
+        .appt
+        .attendees
+        .iter()
+        .map(|attendee| attendee.handle.as_str())
+        .collect();
+    assert_eq!(handles, vec!["bob-smith", "carol"]);
+    assert_eq!(book.select(|_, _| true).len(), 2);
+
+    let carol: Vec<&str> = book
+        .attending("carol")
+        .iter()
+        .map(|appt| appt.description.as_str())
+        .collect();
+    assert_eq!(carol, vec!["Gym", "Planning"]);
+
+    let hits = book.select_participating(|_, appt| appt.id == id);
+    let owners: Vec<(&str, &str)> = hits
+        .iter()
+        .map(|hit| (hit.owner.handle.as_str(), hit.organizer.handle.as_str()))
+        .collect();
+    assert_eq!(
+        owners,
+        vec![
+            ("alice", "alice"),
+            ("bob-smith", "alice"),
+            ("carol", "alice")
+        ]
+    );
+
+    // Declining takes it off the attendee's schedule
+    book.owners[0].appointments[0].attendees[0].response = Response::Declined;
+    assert!(book.attending("bob-smith").is_empty());
+
+    assert!(book.merge("carol", "alice").is_ok());
+    assert!(book.find_id(id).unwrap().appt.attendees.len() == 1);
+    book.remove_owner("bob smith");
+    assert!(book.find_id(id).unwrap().appt.attendees.is_empty());
+}
@@ -0,0 +1,857 @@
+use crate::appointment::Appointment;
+use crate::book::{Book, Hit};
+use crate::hooks::{self, Action};
+use crate::html::escape;
+use crate::ical;
+use crate::owner::Owner;
+use crate::server::{self, Request, Response};
+use chrono::NaiveDateTime;
+use regex::Regex;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+const XML: &str = "application/xml; charset=utf-8";
+const CALENDAR: &str = "text/calendar; charset=utf-8";
+const TEXT: &str = "text/plain; charset=utf-8";
+
+// The resources a path can name. "/" is the principal, "/calendars/" holds a calendar per owner
+// at "/calendars/{handle}/", and each appointment is an event resource named after its UID at
+// "/calendars/{handle}/{uid}.ics". Events are identified by handle and UID since they may not
+// exist yet
+enum Resource {
+    Principal,
+    Home,
+    Calendar(String),
+    Event(String, String),
+}
+
+// Route a CalDAV request to the book. Returns the response and whether the book was modified
+pub fn handle(request: &Request, apptbook: &mut Book) -> (Response, bool) {
+    // Clients discover the server through the well-known URL
+    if request.path.trim_end_matches('/') == "/.well-known/caldav" {
+        return (
+            Response::new(301, TEXT, String::new()).with_header("Location", "/"),
+            false,
+        );
+    }
+
+    let resource = match resolve(&request.path, apptbook) {
+        Some(resource) => resource,
+        None => return (error(404, "Not found"), false),
+    };
+
+    match request.method.as_str() {
+        "OPTIONS" => (
+            Response::new(200, TEXT, String::new())
+                .with_header("DAV", "1, 3, calendar-access")
+                .with_header("Allow", "OPTIONS, GET, PUT, DELETE, PROPFIND, REPORT"),
+            false,
+        ),
+        "PROPFIND" => (propfind(request, apptbook, &resource), false),
+        "REPORT" => (report(request, apptbook, &resource), false),
+        "GET" => (get(apptbook, &resource), false),
+        "PUT" => put(request, apptbook, &resource),
+        "DELETE" => delete(request, apptbook, &resource),
+        _ => (error(405, "Method not allowed"), false),
+    }
+}
+
+fn resolve(path: &str, apptbook: &Book) -> Option<Resource> {
+    let segments: Vec<String> = path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(server::decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    let handle_of = |owner: &str| apptbook.find(owner).map(|owner| owner.handle.clone());
+
+    match segments.as_slice() {
+        [] => Some(Resource::Principal),
+        ["calendars"] => Some(Resource::Home),
+        ["calendars", owner] => handle_of(owner).map(Resource::Calendar),
+        ["calendars", owner, name] => {
+            let uid = name.strip_suffix(".ics").unwrap_or(name).to_string();
+            handle_of(owner).map(|handle| Resource::Event(handle, uid))
+        }
+        _ => None,
+    }
+}
+
+fn propfind(request: &Request, apptbook: &Book, resource: &Resource) -> Response {
+    // A missing Depth means infinity, which is treated like 1
+    let children = request.header("Depth") != Some("0");
+    let mut responses = Vec::new();
+
+    match resource {
+        Resource::Principal => {
+            responses.push(propstat("/", &principal_props()));
+
+            if children {
+                responses.push(propstat("/calendars/", &home_props()));
+            }
+        }
+        Resource::Home => {
+            responses.push(propstat("/calendars/", &home_props()));
+
+            if children {
+                for owner in &apptbook.owners {
+                    responses.push(propstat(
+                        &calendar_href(owner),
+                        &calendar_props(apptbook, owner),
+                    ));
+                }
+            }
+        }
+        Resource::Calendar(handle) => {
+            let owner = apptbook.find(handle).unwrap();
+            responses.push(propstat(
+                &calendar_href(owner),
+                &calendar_props(apptbook, owner),
+            ));
+
+            if children {
+                for appt in apptbook.attending(&owner.handle) {
+                    responses.push(propstat(&event_href(owner, appt), &event_props(appt)));
+                }
+            }
+        }
+        Resource::Event(handle, uid) => match find_event(apptbook, handle, uid) {
+            Some(hit) => responses.push(propstat(
+                &event_href(hit.owner, hit.appt),
+                &event_props(hit.appt),
+            )),
+            None => return error(404, "No such event"),
+        },
+    }
+
+    multistatus(&responses)
+}
+
+// Answer a calendar-query (optionally limited to a time range) or a calendar-multiget of event
+// hrefs with the matching events' data
+fn report(request: &Request, apptbook: &Book, resource: &Resource) -> Response {
+    let handle = match resource {
+        Resource::Calendar(handle) => handle,
+        _ => return error(403, "Reports are only supported on calendars"),
+    };
+
+    let owner = apptbook.find(handle).unwrap();
+    let mut responses = Vec::new();
+
+    if request.body.contains("calendar-multiget") {
+        let href_re = Regex::new(r"<(?:[\w-]+:)?href[^>]*>([^<]*)</(?:[\w-]+:)?href>").unwrap();
+
+        for captures in href_re.captures_iter(&request.body) {
+            let href = unescape(captures[1].trim());
+
+            // Hrefs may be full URLs
+            let path = match href.find("://") {
+                Some(index) => match href[index + 3..].find('/') {
+                    Some(slash) => href[index + 3 + slash..].to_string(),
+                    None => "/".to_string(),
+                },
+                None => href.clone(),
+            };
+
+            let hit = match resolve(&path, apptbook) {
+                Some(Resource::Event(handle, uid)) => find_event(apptbook, &handle, &uid),
+                _ => None,
+            };
+
+            match hit {
+                Some(hit) => responses.push(event_data(&hit)),
+                None => responses.push(format!(
+                    "<D:response><D:href>{}</D:href><D:status>HTTP/1.1 404 Not Found</D:status></D:response>",
+                    escape(&href)
+                )),
+            }
+        }
+    } else if request.body.contains("calendar-query") {
+        let (start, end) = match time_range(&request.body) {
+            Ok(range) => range,
+            Err(message) => return error(400, &message),
+        };
+
+        let hits = apptbook.select_participating(|other, appt| {
+            other.handle == owner.handle && overlaps(appt, start, end)
+        });
+
+        for hit in hits {
+            responses.push(event_data(&hit));
+        }
+    } else {
+        return error(403, "Unsupported report");
+    }
+
+    multistatus(&responses)
+}
+
+fn get(apptbook: &Book, resource: &Resource) -> Response {
+    match resource {
+        Resource::Calendar(handle) => {
+            let hits = apptbook.select_participating(|owner, _| &owner.handle == handle);
+            Response::new(200, CALENDAR, ical::calendar(&hits))
+        }
+        Resource::Event(handle, uid) => match find_event(apptbook, handle, uid) {
+            Some(hit) => {
+                let etag = etag(hit.appt);
+                Response::new(200, CALENDAR, ical::calendar(&[hit])).with_header("ETag", &etag)
+            }
+            None => error(404, "No such event"),
+        },
+        _ => error(405, "Method not allowed"),
+    }
+}
+
+// Create or replace an event. An event already in the book under the UID is replaced, even if
+// it's in another owner's calendar, which moves it to this one
+fn put(request: &Request, apptbook: &mut Book, resource: &Resource) -> (Response, bool) {
+    let (handle, uid) = match resource {
+        Resource::Event(handle, uid) => (handle, uid),
+        _ => return (error(405, "Method not allowed"), false),
+    };
+
+    let event = match ical::parse_event(&request.body) {
+        Ok(event) => event,
+        Err(message) => return (error(400, &message), false),
+    };
+
+    let existing = apptbook
+        .select(|_, appt| &ical::uid(appt) == uid)
+        .pop()
+        .map(|hit| (hit.appt.id, etag(hit.appt)));
+    let existing_appt = existing
+        .as_ref()
+        .map(|(id, _)| apptbook.find_id(*id).unwrap());
+
+    if let Some(response) = check_preconditions(request, existing.as_ref().map(|(_, etag)| etag)) {
+        return (response, false);
+    }
+
+    let mut appt = Appointment::new(&event.summary, event.start, event.end).unwrap();
+    appt.reminders = event.reminders;
+    appt.tags = event.tags;
+    appt.location = event.location;
+    appt.notes = event.notes;
+    appt.status = event.status.unwrap_or_default();
+    appt.all_day = event.all_day;
+
+    let hooks = match server::load_hooks() {
+        Ok(hooks) => hooks,
+        Err(message) => return (error(403, &message), false),
+    };
+
+    // Clients don't send attendees, so they're kept. An attendee changing the event from their
+    // calendar doesn't take it from its organizer
+    let organizer = match existing_appt {
+        Some(hit) => {
+            appt.uid = hit.appt.uid.clone();
+            appt.attendees = hit.appt.attendees.clone();
+
+            // Completed appointments and no-shows are published as confirmed, so they stay as
+            // they are unless the client changed the status. Changes follow the lifecycle
+            let mut current = hit.appt.clone();
+
+            appt.status = match event.status {
+                Some(status) if ical::status_name(status) != ical::status_name(current.status) => {
+                    if let Err(message) = current.set_status(status) {
+                        return (error(403, &message), false);
+                    }
+
+                    status
+                }
+                _ => current.status,
+            };
+
+            if hit.appt.is_attending(handle) {
+                hit.owner.handle.clone()
+            } else {
+                handle.clone()
+            }
+        }
+        None => handle.clone(),
+    };
+
+    let (result, status) = match existing {
+        Some((id, _)) => (
+            hooks::replace(&hooks, apptbook, Action::Edit, id, &organizer, appt),
+            204,
+        ),
+        None => {
+            // Clients name events after their UID, but keep the UID from the event if not
+            appt.uid = Some(event.uid.unwrap_or_else(|| uid.clone()));
+            (hooks::add(&hooks, apptbook, Action::Add, handle, appt), 201)
+        }
+    };
+
+    let id = match result {
+        Ok(id) => id,
+        Err(message) => return (error(403, &message), false),
+    };
+
+    let hit = apptbook.find_id(id).unwrap();
+    let mut response =
+        Response::new(status, TEXT, String::new()).with_header("ETag", &etag(hit.appt));
+
+    if &ical::uid(hit.appt) != uid {
+        response = response.with_header("Location", &event_href(hit.owner, hit.appt));
+    }
+
+    (response, true)
+}
+
+fn delete(request: &Request, apptbook: &mut Book, resource: &Resource) -> (Response, bool) {
+    let (handle, uid) = match resource {
+        Resource::Event(handle, uid) => (handle, uid),
+        // Calendars are owners, which are managed from the menu
+        _ => return (error(403, "Only events can be deleted"), false),
+    };
+
+    let (id, current, organizer) = match find_event(apptbook, handle, uid) {
+        Some(hit) => (hit.appt.id, etag(hit.appt), hit.organizer.handle.clone()),
+        None => return (error(404, "No such event"), false),
+    };
+
+    if let Some(response) = check_preconditions(request, Some(&current)) {
+        return (response, false);
+    }
+
+    // An attendee deleting the event from their calendar declines it, and it stays for everyone
+    // else
+    if &organizer != handle {
+        return match server::load_hooks()
+            .and_then(|hooks| hooks::decline(&hooks, apptbook, id, handle))
+        {
+            Ok(()) => (Response::new(204, TEXT, String::new()), true),
+            Err(message) => (error(403, &message), false),
+        };
+    }
+
+    match server::load_hooks().and_then(|hooks| hooks::delete(&hooks, apptbook, id)) {
+        Ok(()) => (Response::new(204, TEXT, String::new()), true),
+        Err(message) => (error(403, &message), false),
+    }
+}
+
+// Clients avoid overwriting changes by sending If-Match with the ETag they last saw, and avoid
+// replacing an event with If-None-Match: *
+fn check_preconditions(request: &Request, current: Option<&String>) -> Option<Response> {
+    let failed = match (request.header("If-Match"), request.header("If-None-Match")) {
+        (Some(expected), _) => expected != "*" && current.map(String::as_str) != Some(expected),
+        (_, Some("*")) => current.is_some(),
+        _ => false,
+    };
+
+    if failed {
+        Some(error(412, "The event has changed"))
+    } else {
+        None
+    }
+}
+
+fn find_event<'a>(apptbook: &'a Book, handle: &str, uid: &str) -> Option<Hit<'a>> {
+    apptbook
+        .select_participating(|owner, appt| owner.handle == handle && ical::uid(appt) == uid)
+        .pop()
+}
+
+// Parse the time-range of a calendar-query, if it has one. Either end may be left open
+fn time_range(body: &str) -> Result<(Option<NaiveDateTime>, Option<NaiveDateTime>), String> {
+    let range_re = Regex::new(r"<(?:[\w-]+:)?time-range\b([^>]*)>").unwrap();
+    let attributes = match range_re.captures(body) {
+        Some(captures) => captures[1].to_string(),
+        None => return Ok((None, None)),
+    };
+
+    let attribute = |name: &str| {
+        Regex::new(&format!(r#"\b{}\s*=\s*"([^"]*)""#, name))
+            .unwrap()
+            .captures(&attributes)
+            .map(|captures| ical::parse_utc(&captures[1]))
+            .transpose()
+    };
+
+    Ok((attribute("start")?, attribute("end")?))
+}
+
+// Whether the appointment overlaps the range. An instantaneous appointment overlaps if it's
+// within the range
+fn overlaps(appt: &Appointment, start: Option<NaiveDateTime>, end: Option<NaiveDateTime>) -> bool {
+    let before_end = end.is_none_or(|end| appt.start_date_time < end);
+    let after_start =
+        start.is_none_or(|start| appt.end_date_time > start || appt.start_date_time >= start);
+
+    before_end && after_start
+}
+
+// Changes whenever the appointment does, so clients know to fetch it again
+// Covers every field ical::calendar publishes, so clients refetch an event whenever any of them
+// changes
+fn etag(appt: &Appointment) -> String {
+    let mut hasher = DefaultHasher::new();
+    (
+        appt.id,
+        &appt.uid,
+        &appt.description,
+        appt.start_date_time,
+        appt.end_date_time,
+        appt.status,
+        &appt.location,
+        &appt.notes,
+        &appt.tags,
+        &appt.reminders,
+        appt.all_day,
+        &appt.attendees,
+    )
+        .hash(&mut hasher);
+
+    format!("\"{:016x}\"", hasher.finish())
+}
+
+fn calendar_href(owner: &Owner) -> String {
+    format!("/calendars/{}/", encode(&owner.handle))
+}
+
+fn event_href(owner: &Owner, appt: &Appointment) -> String {
+    format!(
+        "/calendars/{}/{}.ics",
+        encode(&owner.handle),
+        encode(&ical::uid(appt))
+    )
+}
+
+fn principal_props() -> String {
+    "<D:resourcetype><D:collection/><D:principal/></D:resourcetype>\
+     <D:displayname>apptbook</D:displayname>\
+     <D:current-user-principal><D:href>/</D:href></D:current-user-principal>\
+     <D:principal-URL><D:href>/</D:href></D:principal-URL>\
+     <C:calendar-home-set><D:href>/calendars/</D:href></C:calendar-home-set>"
+        .to_string()
+}
+
+fn home_props() -> String {
+    "<D:resourcetype><D:collection/></D:resourcetype>\
+     <D:displayname>Calendars</D:displayname>\
+     <D:current-user-principal><D:href>/</D:href></D:current-user-principal>"
+        .to_string()
+}
+
+fn calendar_props(apptbook: &Book, owner: &Owner) -> String {
+    // The ctag changes whenever any event in the calendar does
+    let mut hasher = DefaultHasher::new();
+    for appt in apptbook.attending(&owner.handle) {
+        etag(appt).hash(&mut hasher);
+    }
+    let ctag = format!("\"{:016x}\"", hasher.finish());
+
+    let mut props = format!(
+        "<D:resourcetype><D:collection/><C:calendar/></D:resourcetype>\
+         <D:displayname>{}</D:displayname>\
+         <C:supported-calendar-component-set><C:comp name=\"VEVENT\"/></C:supported-calendar-component-set>\
+         <D:current-user-privilege-set><D:privilege><D:read/></D:privilege><D:privilege><D:write/></D:privilege></D:current-user-privilege-set>\
+         <CS:getctag>{}</CS:getctag><D:getetag>{}</D:getetag>",
+        escape(&owner.name),
+        ctag,
+        ctag
+    );
+
+    if let Some(color) = &owner.preferences.color {
+        props.push_str(&format!(
+            "<I:calendar-color>{}</I:calendar-color>",
+            escape(color)
+        ));
+    }
+
+    props
+}
+
+fn event_props(appt: &Appointment) -> String {
+    format!(
+        "<D:resourcetype/><D:getetag>{}</D:getetag>\
+         <D:getcontenttype>text/calendar; charset=utf-8; component=VEVENT</D:getcontenttype>",
+        escape(&etag(appt))
+    )
+}
+
+fn event_data(hit: &Hit) -> String {
+    propstat(
+        &event_href(hit.owner, hit.appt),
+        &format!(
+            "<D:getetag>{}</D:getetag><C:calendar-data>{}</C:calendar-data>",
+            escape(&etag(hit.appt)),
+            escape(&ical::calendar(std::slice::from_ref(hit)))
+        ),
+    )
+}
+
+fn propstat(href: &str, props: &str) -> String {
+    format!(
+        "<D:response><D:href>{}</D:href><D:propstat><D:prop>{}</D:prop>\
+         <D:status>HTTP/1.1 200 OK</D:status></D:propstat></D:response>",
+        escape(href),
+        props
+    )
+}
+
+fn multistatus(responses: &[String]) -> Response {
+    let mut body = "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n\
+                    <D:multistatus xmlns:D=\"DAV:\" xmlns:C=\"urn:ietf:params:xml:ns:caldav\" \
+                    xmlns:CS=\"http://calendarserver.org/ns/\" xmlns:I=\"http://apple.com/ns/ical/\">\n"
+        .to_string();
+
+    for response in responses {
+        body.push_str(response);
+        body.push('\n');
+    }
+
+    body.push_str("</D:multistatus>\n");
+
+    Response::new(207, XML, body)
+}
+
+fn error(status: u16, message: &str) -> Response {
+    Response::new(status, TEXT, format!("{}\n", message))
+}
+
+// Percent-encode a path segment, keeping characters that are safe in URLs
+fn encode(segment: &str) -> String {
+    let mut encoded = String::new();
+
+    for byte in segment.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~@".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+// Undo the XML escapes clients may use in hrefs
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn request(method: &str, path: &str, headers: &[(&str, &str)], body: &str) -> Request {
+    Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: String::new(),
+        headers: headers
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+        body: body.to_string(),
+    }
+}
+
+#[cfg(test)]
+fn sample_book() -> Book {
+    Book::sample(&[
+        (
+            "Alice Smith",
+            "Review & plan",
+            "10/18/2026 10:00",
+            "10/18/2026 11:00",
+        ),
+        (
+            "Alice Smith",
+            "Retro",
+            "10/25/2026 15:00",
+            "10/25/2026 16:00",
+        ),
+        ("Bob", "Lunch", "10/18/2026 12:00", "10/18/2026 13:00"),
+    ])
+}
+
+#[test]
+fn propfind_lists_calendars_and_events() {
+    let mut apptbook = sample_book();
+
+    let (response, _) = handle(
+        &request("PROPFIND", "/calendars/", &[("Depth", "1")], ""),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 207);
+    assert!(response
+        .body
+        .contains("<D:href>/calendars/alice-smith/</D:href>"));
+    assert!(response
+        .body
+        .contains("<D:displayname>Alice Smith</D:displayname>"));
+    assert!(response.body.contains("<D:href>/calendars/bob/</D:href>"));
+
+    let (response, _) = handle(
+        &request("PROPFIND", "/calendars/alice-smith/", &[("Depth", "1")], ""),
+        &mut apptbook,
+    );
+    assert!(response
+        .body
+        .contains("<D:href>/calendars/alice-smith/apptbook-1.ics</D:href>"));
+    assert!(response
+        .body
+        .contains("<D:href>/calendars/alice-smith/apptbook-2.ics</D:href>"));
+    assert!(!response.body.contains("apptbook-3"));
+
+    let (response, _) = handle(
+        &request("PROPFIND", "/calendars/alice-smith/", &[("Depth", "0")], ""),
+        &mut apptbook,
+    );
+    assert!(!response.body.contains(".ics"));
+
+    let (response, _) = handle(
+        &request("PROPFIND", "/", &[("Depth", "0")], ""),
+        &mut apptbook,
+    );
+    assert!(response
+        .body
+        .contains("<C:calendar-home-set><D:href>/calendars/</D:href></C:calendar-home-set>"));
+
+    let (response, _) = handle(
+        &request("PROPFIND", "/calendars/carol/", &[], ""),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 404);
+}
+
+#[test]
+fn report_calendar_query_and_multiget() {
+    let mut apptbook = sample_book();
+
+    let query = r#"<?xml version="1.0"?>
+<C:calendar-query xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop><D:getetag/><C:calendar-data/></D:prop>
+  <C:filter><C:comp-filter name="VCALENDAR"><C:comp-filter name="VEVENT">
+    <C:time-range start="20261020T000000Z" end="20261101T000000Z"/>
+  </C:comp-filter></C:comp-filter></C:filter>
+</C:calendar-query>"#;
+
+    let (response, _) = handle(
+        &request(
+            "REPORT",
+            "/calendars/alice-smith/",
+            &[("Depth", "1")],
+            query,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 207);
+    assert!(response.body.contains("SUMMARY:Retro"));
+    assert!(!response.body.contains("Review"));
+
+    let multiget = r#"<C:calendar-multiget xmlns:D="DAV:" xmlns:C="urn:ietf:params:xml:ns:caldav">
+  <D:prop><D:getetag/><C:calendar-data/></D:prop>
+  <D:href>/calendars/alice-smith/apptbook-1.ics</D:href>
+  <D:href>http://localhost:5232/calendars/alice-smith/missing.ics</D:href>
+</C:calendar-multiget>"#;
+
+    let (response, _) = handle(
+        &request("REPORT", "/calendars/alice-smith/", &[], multiget),
+        &mut apptbook,
+    );
+    assert!(response.body.contains("SUMMARY:Review &amp; plan"));
+    assert!(response.body.contains(
+        "<D:href>http://localhost:5232/calendars/alice-smith/missing.ics</D:href><D:status>HTTP/1.1 404 Not Found</D:status>"
+    ));
+}
+
+#[test]
+fn put_and_delete_events() {
+    let mut apptbook = sample_book();
+    let event = "BEGIN:VCALENDAR\r\nVERSION:2.0\r\nBEGIN:VEVENT\r\nUID:abc-123\r\n\
+                 DTSTART:20261019T090000\r\nDTEND:20261019T093000\r\nSUMMARY:Standup\r\n\
+                 END:VEVENT\r\nEND:VCALENDAR\r\n";
+
+    let (response, modified) = handle(
+        &request(
+            "PUT",
+            "/calendars/bob/abc-123.ics",
+            &[("If-None-Match", "*")],
+            event,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 201);
+    assert!(modified);
+
+    let bob = apptbook.find("bob").unwrap();
+    assert_eq!(bob.appointments[1].description, "Standup");
+    assert_eq!(bob.appointments[1].uid.as_deref(), Some("abc-123"));
+
+    // Creating it again is refused, and updates need the current ETag
+    let (response, _) = handle(
+        &request(
+            "PUT",
+            "/calendars/bob/abc-123.ics",
+            &[("If-None-Match", "*")],
+            event,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 412);
+
+    let (response, _) = handle(
+        &request("GET", "/calendars/bob/abc-123.ics", &[], ""),
+        &mut apptbook,
+    );
+    let current = response.headers[0].1.clone();
+    assert!(response.body.contains("UID:abc-123"));
+
+    let (response, _) = handle(
+        &request(
+            "PUT",
+            "/calendars/bob/abc-123.ics",
+            &[("If-Match", "\"stale\"")],
+            event,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 412);
+
+    let (response, _) = handle(
+        &request(
+            "PUT",
+            "/calendars/bob/abc-123.ics",
+            &[("If-Match", &current)],
+            &event.replace("Standup", "Daily standup"),
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 204);
+    assert_eq!(
+        apptbook.find("bob").unwrap().appointments[1].description,
+        "Daily standup"
+    );
+
+    // Appointments added from the menu can be edited too
+    let (response, _) = handle(
+        &request(
+            "PUT",
+            "/calendars/bob/apptbook-3.ics",
+            &[],
+            "BEGIN:VEVENT\nUID:apptbook-3\nDTSTART:20261018T120000\nDTEND:20261018T130000\nEND:VEVENT",
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 204);
+    assert_eq!(apptbook.find_id(3).unwrap().appt.uid, None);
+
+    let (response, _) = handle(
+        &request(
+            "PUT",
+            "/calendars/bob/bad.ics",
+            &[],
+            "BEGIN:VEVENT\nEND:VEVENT",
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 400);
+
+    let (response, modified) = handle(
+        &request("DELETE", "/calendars/bob/abc-123.ics", &[], ""),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 204);
+    assert!(modified);
+    assert_eq!(apptbook.find("bob").unwrap().appointments.len(), 1);
+
+    let (response, _) = handle(
+        &request("DELETE", "/calendars/bob/", &[], ""),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 403);
+}
+
+#[test]
+fn attendees_see_shared_events() {
+    let mut apptbook = sample_book();
+    let mut review = apptbook.find_id(1).unwrap().appt.clone();
+    review.attendees = crate::attendees::parse("bob");
+    apptbook.remove_id(1);
+    apptbook.add("alice-smith", review).unwrap();
+
+    let (response, _) = handle(&request("GET", "/calendars/bob/", &[], ""), &mut apptbook);
+    assert!(response.body.contains("SUMMARY:Review & plan"));
+
+    // Editing from the attendee's calendar keeps the organizer and attendees
+    let (response, _) = handle(
+        &request(
+            "PUT",
+            "/calendars/bob/apptbook-1.ics",
+            &[],
+            "BEGIN:VEVENT\nUID:apptbook-1\nDTSTART:20261018T110000\nDTEND:20261018T120000\n\
+             SUMMARY:Review\nEND:VEVENT",
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 204);
+    let hit = apptbook.find_id(1).unwrap();
+    assert_eq!(hit.owner.handle, "alice-smith");
+    assert_eq!(hit.appt.attendees.len(), 1);
+
+    // Deleting it there declines it
+    let (response, _) = handle(
+        &request("DELETE", "/calendars/bob/apptbook-1.ics", &[], ""),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 204);
+    let hit = apptbook.find_id(1).unwrap();
+    assert_eq!(
+        hit.appt.attendees[0].response,
+        crate::attendees::Response::Declined
+    );
+    assert!(find_event(&apptbook, "bob", "apptbook-1").is_none());
+}
+
+#[test]
+fn etags_cover_published_fields() {
+    let appt = Appointment::default();
+    let changed = |change: fn(&mut Appointment)| {
+        let mut other = appt.clone();
+        change(&mut other);
+        etag(&other) != etag(&appt)
+    };
+
+    assert!(changed(
+        |appt| appt.status = crate::appointment::Status::Cancelled
+    ));
+    assert!(changed(|appt| appt.location = Some("Room 4".to_string())));
+    assert!(changed(|appt| appt.notes = Some("Agenda".to_string())));
+    assert!(changed(|appt| appt.tags = vec!["client".to_string()]));
+    assert!(changed(|appt| appt.reminders = vec![15]));
+    assert!(changed(|appt| appt.all_day = true));
+    assert!(changed(
+        |appt| appt.attendees = crate::attendees::parse("bob")
+    ));
+}
+
+#[test]
+fn time_ranges() {
+    use chrono::NaiveDate;
+
+    let appt = Appointment {
+        start_date_time: NaiveDate::from_ymd(2026, 10, 18).and_hms(10, 0, 0),
+        end_date_time: NaiveDate::from_ymd(2026, 10, 18).and_hms(11, 0, 0),
+        ..Default::default()
+    };
+    let at = |hour| Some(NaiveDate::from_ymd(2026, 10, 18).and_hms(hour, 0, 0));
+
+    assert!(overlaps(&appt, at(10), at(12)));
+    assert!(overlaps(&appt, None, at(11)));
+    assert!(!overlaps(&appt, at(11), None));
+    assert!(!overlaps(&appt, at(8), at(10)));
+    assert_eq!(time_range("<C:filter/>").unwrap(), (None, None));
+    assert!(time_range(r#"<C:time-range start="tomorrow"/>"#).is_err());
+    assert_eq!(encode("a b@c"), "a%20b@c");
+}
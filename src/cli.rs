@@ -0,0 +1,1183 @@
+use crate::agenda;
+use crate::appointment::{self, Status};
+use crate::archive;
+use crate::attendees::{self, Response};
+use crate::book::{self, Book, Hit};
+use crate::caldav;
+use crate::config::{self, Config, Hooks, Reminders, Retention};
+use crate::csv;
+use crate::filter::Filter;
+use crate::grid;
+use crate::hooks::{self, Action};
+use crate::html;
+use crate::ical;
+use crate::jsonl;
+use crate::output;
+use crate::prompt;
+use crate::remind::{self, Fired, Sink};
+use crate::report::{self, WorkingHours};
+use crate::search::{self, Pattern, Query};
+use crate::server;
+use crate::tags;
+use crate::templates::{self, Template};
+use crate::tui;
+use chrono::prelude::*;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::io::Read;
+
+const USAGE: &str = "Usage:
+    apptbook                          Start the interactive menu
+    apptbook tui                      Start the full-screen terminal interface
+    apptbook list [filter] [options]  List upcoming appointments matching a filter expression, e.g.
+                                      owner:alice after:2026-11-01 \"duration>60\" text:review
+                                      status:tentative
+        --history                     Include past appointments
+        --archived                    List archived appointments instead
+        --hide-cancelled              Leave out cancelled appointments
+        --output <format>             Print as json, csv, table or plain (tab-separated)
+    apptbook today [owner...]         Show today's appointments for some owners (default: everyone)
+        --hide-cancelled              Leave out cancelled appointments
+        --output <format>             Print as json, csv, table or plain (tab-separated)
+    apptbook tomorrow [owner...]      Show tomorrow's appointments, with the same options
+    apptbook next [owner...]          Show the next appointment to start, with a countdown.
+                                      Cancelled appointments are skipped
+        --output <format>             Print as json, csv, table or plain (tab-separated)
+    apptbook reminders <id> [<offsets>]
+                                      Show or set an appointment's reminders, e.g. 15m,1h,1d,
+                                      or none to remove them
+    apptbook tags [--output <format>] List tags with how many appointments have each
+    apptbook tags set <id> <tags>     Set an appointment's tags, comma-separated, or none
+    apptbook tags rename <tag> <new>  Rename a tag on every appointment. Renaming to a tag
+                                      that's already used merges them
+    apptbook tags merge <tag>... <into>
+                                      Merge several tags into one
+    apptbook template list [--output <format>]
+                                      List appointment templates
+    apptbook template add <name> <description> <minutes> [options]
+                                      Add a template, which the add menu offers so only an owner
+                                      and a start need to be entered
+        --tags <tags>                 Tags for appointments from the template, comma-separated
+        --reminders <offsets>         Reminders for appointments from the template, e.g. 15m,1h
+    apptbook template delete <name>   Delete a template
+    apptbook attendees <id>           Show who's attending an appointment, with their responses
+    apptbook attendees <id> add <owner>...
+                                      Invite owners to an appointment. It's stored once and shown
+                                      to everyone attending
+    apptbook attendees <id> remove <owner>...
+                                      Take owners off an appointment
+    apptbook respond <id> <owner> <response>
+                                      Record an attendee's response: accepted, declined or
+                                      tentative
+    apptbook status <id> [<status>]   Show or change an appointment's status. Tentative
+                                      appointments can be confirmed or cancelled, and confirmed
+                                      ones completed, cancelled or marked no-show
+    apptbook remind [options]         Send the reminders that are due and haven't been sent yet.
+                                      Reminders print to standard output by default
+        --notify <command>            Run a command with each reminder's title and message,
+                                      e.g. notify-send
+        --mail <path>                 Append reminders to a mailbox file, e.g. /var/mail/alice
+        --watch                       Keep checking until interrupted
+        --interval <seconds>          How often to check with --watch (default: 60)
+    apptbook week [owner...] [options]
+                                      Draw a week as a grid of days by hours for some owners
+                                      (default: everyone)
+        --date <mm/dd/yyyy>           Draw the week containing this date (default: today)
+        --width <n>                   Width of the grid in columns (default: 80)
+        --hide-cancelled              Leave out cancelled appointments
+    apptbook month [owner...] [options]
+                                      Draw a month as a calendar grid, with the same options
+    apptbook owners [--output <format>]
+                                      List appointment owners
+    apptbook archive --days <n>       Archive appointments that ended more than n days ago
+    apptbook delete <filter> [options]
+                                      Delete every appointment matching a filter expression
+        --dry-run                     Show what would be deleted without deleting it
+        --yes                         Don't ask for confirmation
+    apptbook export [filter] --format <format> [options]
+                                      Export appointments matching a filter expression
+        --format csv                  Columns owner, description, start, end, duration_minutes
+        --format html                 Standalone calendar page of month and week grids
+        --format ics                  iCalendar file for calendar applications
+        --format jsonl                One JSON object per line (see README for the fields)
+        --format markdown             Agenda with a heading per day
+        --format org                  Org-mode entries with active timestamps
+        --file <path>                 Write to a file instead of standard output
+        --archived                    Export archived appointments instead
+        --delimiter <char>            CSV field delimiter (default: ,)
+        --date-format <format>        chrono format for dates (default: %m/%d/%Y %H:%M)
+        --title <title>               HTML page title (default: Appointment Book)
+        --view <view>                 HTML grids to include: month, week or both (default)
+    apptbook import <path> --format <format> [options]
+                                      Import appointments from a file, or - for standard input
+        --format csv                  Columns are matched by header name
+        --format jsonl                One JSON object per line. Lines with the ID of an
+                                      existing appointment replace it
+        --columns <mapping>           Header names for each field, e.g. owner=Name,start=Begins
+        --delimiter <char>            CSV field delimiter (default: ,)
+        --date-format <format>        chrono format for dates (default: %m/%d/%Y %H:%M)
+        --dry-run                     Validate every row without importing
+    apptbook search <query> [options] Search appointment descriptions
+        --regex                       Treat the query as a regular expression
+        --from <mm/dd/yyyy>           Only match appointments on or after this date
+        --to <mm/dd/yyyy>             Only match appointments on or before this date
+        --tag <tags>                  Only match appointments with these tags, comma-separated
+        --archived                    Search archived appointments instead
+        --output <format>             Print as json, csv, table or plain (tab-separated)
+    apptbook report [filter] [options]
+                                      Summarize appointments per owner: count, total and average
+                                      duration, busiest weekdays and hours, longest gap and
+                                      share of working hours booked
+        --from <mm/dd/yyyy>           First day of the report (default: first of this month)
+        --to <mm/dd/yyyy>             Last day of the report (default: last of this month)
+        --hours <hh:mm-hh:mm>         Working hours, Monday to Friday (default: 09:00-17:00)
+        --output <format>             Print as text (default) or json
+    apptbook serve [--port <n>]       Serve a JSON REST API on localhost (default port: 8080)
+    apptbook caldav [--port <n>]      Serve a calendar per owner to CalDAV clients on localhost
+                                      (default port: 5232)";
+
+// Command-line arguments split into positional arguments and --options
+pub struct Args {
+    pub positional: Vec<String>,
+    options: HashMap<String, Option<String>>,
+}
+
+impl Args {
+    // Options listed in switches take no value. Every other option consumes the next argument
+    pub fn parse(args: &[String], switches: &[&str]) -> Result<Args, String> {
+        let mut positional = Vec::new();
+        let mut options = HashMap::new();
+        let mut iter = args.iter();
+
+        while let Some(arg) = iter.next() {
+            if let Some(name) = arg.strip_prefix("--") {
+                if switches.contains(&name) {
+                    options.insert(name.to_string(), None);
+                } else {
+                    match iter.next() {
+                        Some(value) => options.insert(name.to_string(), Some(value.clone())),
+                        None => return Err(format!("Missing value for --{}", name)),
+                    };
+                }
+            } else {
+                positional.push(arg.clone());
+            }
+        }
+
+        Ok(Args {
+            positional,
+            options,
+        })
+    }
+
+    pub fn flag(&self, name: &str) -> bool {
+        self.options.contains_key(name)
+    }
+
+    pub fn value(&self, name: &str) -> Option<&str> {
+        self.options.get(name).and_then(|value| value.as_deref())
+    }
+}
+
+// Run a command given on the command line, with the settings from the config file. Returns
+// whether the book was modified and should be saved
+pub fn run(args: &[String], apptbook: &mut Book, config: &Config) -> Result<bool, String> {
+    let hooks = &config.hooks;
+
+    match args[0].as_str() {
+        "tui" => tui::run(apptbook, hooks),
+        "list" => list_command(&args[1..], apptbook),
+        "today" => day_command(&args[1..], apptbook, 0),
+        "tomorrow" => day_command(&args[1..], apptbook, 1),
+        "next" => next_command(&args[1..], apptbook),
+        "remind" => remind_command(&args[1..], &config.reminders),
+        "reminders" => reminders_command(&args[1..], apptbook, hooks),
+        "tags" => tags_command(&args[1..], apptbook, hooks),
+        "template" => template_command(&args[1..], apptbook),
+        "attendees" => attendees_command(&args[1..], apptbook, hooks),
+        "respond" => respond_command(&args[1..], apptbook, hooks),
+        "status" => status_command(&args[1..], apptbook, hooks),
+        "week" => grid_command(&args[1..], apptbook, grid::week),
+        "month" => grid_command(&args[1..], apptbook, grid::month),
+        "owners" => owners_command(&args[1..], apptbook),
+        "archive" => archive_command(&args[1..], apptbook),
+        "export" => export_command(&args[1..], apptbook),
+        "import" => import_command(&args[1..], apptbook, hooks),
+        "delete" => delete_command(&args[1..], apptbook, hooks),
+        "search" => search_command(&args[1..], apptbook),
+        "report" => report_command(&args[1..], apptbook),
+        "serve" => serve_command(&args[1..], 8080, server::handle),
+        "caldav" => serve_command(&args[1..], 5232, caldav::handle),
+        "help" | "--help" | "-h" => {
+            println!("{}", USAGE);
+            Ok(false)
+        }
+        command => Err(format!("Unknown command: {}\n\n{}", command, USAGE)),
+    }
+}
+
+fn list_command(args: &[String], apptbook: &Book) -> Result<bool, String> {
+    let args = Args::parse(args, &["history", "archived", "hide-cancelled"])?;
+    let filter = Filter::from_args(&args.positional)?;
+
+    let archive;
+    let (apptbook, history) = if args.flag("archived") {
+        archive = archive::load(archive::ARCHIVE_FILE);
+        (&archive, true)
+    } else {
+        (apptbook, args.flag("history"))
+    };
+
+    // Only upcoming appointments are listed unless history is requested
+    let now = Local::now().naive_local();
+    let hits: Vec<Hit> = filter
+        .select(apptbook)
+        .into_iter()
+        .filter(|hit| history || hit.appt.end_date_time >= now)
+        .filter(|hit| !(args.flag("hide-cancelled") && hit.appt.is_cancelled()))
+        .collect();
+
+    if let Some(format) = output_format(&args)? {
+        print!("{}", output::appointments(&hits, format));
+        return Ok(false);
+    }
+
+    if hits.is_empty() {
+        println!("No appointments matched the filter");
+    }
+
+    for hit in hits {
+        println!("\n> {} ({})", hit.appt.description, hit.owner.name);
+        println!("| {}", hit.appt.formatted_span());
+        println!("| Duration: {}", hit.appt.formatted_duration());
+
+        if let Some(location) = &hit.appt.location {
+            println!("| Location: {}", location);
+        }
+
+        if !hit.appt.status.is_confirmed() {
+            println!("| Status: {}", hit.appt.status.name());
+        }
+
+        attendees::print(apptbook, &hit.owner.handle, hit.appt);
+
+        if !hit.appt.tags.is_empty() {
+            println!("| Tags: {}", hit.appt.tags.join(", "));
+        }
+
+        if !hit.appt.reminders.is_empty() {
+            println!(
+                "| Reminders: {} before",
+                remind::format_offsets(&hit.appt.reminders)
+            );
+        }
+
+        if let Some(notes) = &hit.appt.notes {
+            println!("| Notes:");
+
+            for line in notes.lines() {
+                println!("|   {}", line);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+fn grid_command(
+    args: &[String],
+    apptbook: &Book,
+    draw: fn(&[Hit], NaiveDate, &grid::Options) -> String,
+) -> Result<bool, String> {
+    let args = Args::parse(args, &["hide-cancelled"])?;
+    let today = Local::now().naive_local().date();
+
+    let day = match args.value("date") {
+        Some(date) => appointment::parse_date(date)?,
+        None => today,
+    };
+    let width = match args.value("width").map(str::parse) {
+        Some(Ok(width)) => width,
+        Some(Err(_)) => return Err(format!("Expected --width <n>\n\n{}", USAGE)),
+        None => 80,
+    };
+
+    let hits = owner_hits(apptbook, &args)?;
+
+    print!("{}", draw(&hits, day, &grid::Options { width, today }));
+
+    Ok(false)
+}
+
+// The appointments of the owners named in the arguments, or of everyone if none are named.
+// Cancelled appointments are left out with --hide-cancelled
+fn owner_hits<'a>(apptbook: &'a Book, args: &Args) -> Result<Vec<Hit<'a>>, String> {
+    let hide_cancelled = args.flag("hide-cancelled");
+    let mut handles = Vec::new();
+
+    for name in &args.positional {
+        match apptbook.find(name) {
+            Some(owner) => handles.push(owner.handle.clone()),
+            None => return Err(format!("No owner named {}", name)),
+        }
+    }
+
+    Ok(apptbook.select_participating(|owner, appt| {
+        (handles.is_empty() || handles.contains(&owner.handle))
+            && !(hide_cancelled && appt.is_cancelled())
+    }))
+}
+
+// Show the appointments on the day some number of days from today
+fn day_command(args: &[String], apptbook: &Book, days: i64) -> Result<bool, String> {
+    let args = Args::parse(args, &["hide-cancelled"])?;
+    let hits = owner_hits(apptbook, &args)?;
+    let now = Local::now().naive_local();
+    let day = now.date() + chrono::Duration::days(days);
+
+    match output_format(&args)? {
+        Some(format) => print!(
+            "{}",
+            output::appointments(&agenda::on_day(&hits, day), format)
+        ),
+        None => print!("{}", agenda::day(&hits, day, now)),
+    }
+
+    Ok(false)
+}
+
+fn next_command(args: &[String], apptbook: &Book) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+    let hits = owner_hits(apptbook, &args)?;
+    let now = Local::now().naive_local();
+
+    match output_format(&args)? {
+        Some(format) => print!(
+            "{}",
+            output::appointments(&agenda::upcoming(&hits, now), format)
+        ),
+        None => print!("{}", agenda::next(&hits, now)),
+    }
+
+    Ok(false)
+}
+
+fn reminders_command(args: &[String], apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let (id, offsets) = match args.positional.as_slice() {
+        [id] => (id, None),
+        [id, offsets] => (id, Some(offsets)),
+        _ => return Err(format!("Expected an appointment ID\n\n{}", USAGE)),
+    };
+
+    let id: u64 = id
+        .parse()
+        .map_err(|_| format!("Invalid appointment ID: {}", id))?;
+
+    if apptbook.find_id(id).is_none() {
+        return Err(format!("No appointment with ID {}", id));
+    }
+
+    if let Some(offsets) = offsets {
+        let offsets = remind::parse_offsets(offsets)?;
+        let hit = apptbook.find_id(id).unwrap();
+        let handle = hit.owner.handle.clone();
+        let mut appt = hit.appt.clone();
+        appt.reminders = offsets;
+
+        hooks::replace(hooks, apptbook, Action::Edit, id, &handle, appt)?;
+    }
+
+    let appt = apptbook.find_id(id).unwrap().appt;
+
+    if appt.reminders.is_empty() {
+        println!("{} has no reminders", appt.description);
+    } else {
+        println!(
+            "{} reminds {} before",
+            appt.description,
+            remind::format_offsets(&appt.reminders)
+        );
+    }
+
+    Ok(offsets.is_some())
+}
+
+fn tags_command(args: &[String], apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let (from, into) = match args.positional.as_slice() {
+        [] => {
+            let counts = tags::counts(apptbook);
+
+            if let Some(format) = output_format(&args)? {
+                print!("{}", output::tags(&counts, format));
+                return Ok(false);
+            }
+
+            if counts.is_empty() {
+                println!("No appointments have tags");
+            }
+
+            for (tag, count) in counts {
+                println!("* {} ({})", tag, count);
+            }
+
+            return Ok(false);
+        }
+        [command, id, tags] if command == "set" => {
+            let id: u64 = id
+                .parse()
+                .map_err(|_| format!("Invalid appointment ID: {}", id))?;
+            let hit = apptbook
+                .find_id(id)
+                .ok_or_else(|| format!("No appointment with ID {}", id))?;
+            let handle = hit.owner.handle.clone();
+            let mut appt = hit.appt.clone();
+            appt.tags = tags::parse(tags);
+
+            hooks::replace(hooks, apptbook, Action::Edit, id, &handle, appt)?;
+            println!("Tags updated successfully");
+
+            return Ok(true);
+        }
+        [command, from, into] if command == "rename" => (vec![from.clone()], into),
+        [command, from @ .., into] if command == "merge" && !from.is_empty() => {
+            (from.to_vec(), into)
+        }
+        _ => return Err(format!("Expected a tags command\n\n{}", USAGE)),
+    };
+
+    if tags::normalize(into).is_empty() {
+        return Err("Tag cannot be empty".to_string());
+    }
+
+    let (changed, refusals) = tags::rename(hooks, apptbook, &from, into);
+
+    for refusal in &refusals {
+        println!("{}", refusal);
+    }
+
+    println!("{} appointment(s) retagged", changed);
+
+    Ok(changed > 0)
+}
+
+fn template_command(args: &[String], apptbook: &mut Book) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    match args.positional.as_slice() {
+        [command] if command == "list" => {
+            if let Some(format) = output_format(&args)? {
+                print!("{}", output::templates(&apptbook.templates, format));
+                return Ok(false);
+            }
+
+            if apptbook.templates.is_empty() {
+                println!("No templates");
+            }
+
+            for template in &apptbook.templates {
+                println!("* {}", template.summary());
+            }
+
+            Ok(false)
+        }
+        [command, name, description, minutes] if command == "add" => {
+            let template = Template::new(
+                name,
+                description,
+                minutes,
+                args.value("tags").unwrap_or(""),
+                args.value("reminders").unwrap_or(""),
+            )?;
+
+            templates::add(apptbook, template)?;
+            println!("Template added successfully");
+
+            Ok(true)
+        }
+        [command, name] if command == "delete" => {
+            if !templates::delete(apptbook, name) {
+                return Err(format!("No template named {}", name));
+            }
+
+            println!("Template deleted successfully");
+
+            Ok(true)
+        }
+        _ => Err(format!("Expected a template command\n\n{}", USAGE)),
+    }
+}
+
+fn attendees_command(args: &[String], apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let (id, command, names) = match args.positional.as_slice() {
+        [id] => (id, None, &[][..]),
+        [id, command, names @ ..] if !names.is_empty() => (id, Some(command.as_str()), names),
+        _ => return Err(format!("Expected an appointment ID\n\n{}", USAGE)),
+    };
+
+    let id: u64 = id
+        .parse()
+        .map_err(|_| format!("Invalid appointment ID: {}", id))?;
+    let hit = apptbook
+        .find_id(id)
+        .ok_or_else(|| format!("No appointment with ID {}", id))?;
+
+    let mut handles = Vec::new();
+
+    for name in names {
+        match apptbook.find(name) {
+            Some(owner) => handles.push(owner.handle.clone()),
+            None => return Err(format!("No owner named {}", name)),
+        }
+    }
+
+    let handle = hit.owner.handle.clone();
+    let mut appt = hit.appt.clone();
+
+    match command {
+        None => {
+            println!("Organizer: {}", hit.owner.name);
+
+            if appt.attendees.is_empty() {
+                println!("{} has no attendees", appt.description);
+            } else {
+                println!("Attendees: {}", attendees::describe(apptbook, &appt));
+            }
+
+            return Ok(false);
+        }
+        Some("add") => {
+            if handles.contains(&handle) {
+                return Err(format!("{} organizes the appointment", hit.owner.name));
+            }
+
+            for handle in handles {
+                if !appt
+                    .attendees
+                    .iter()
+                    .any(|attendee| attendee.handle == handle)
+                {
+                    appt.attendees.push(attendees::Attendee::new(&handle));
+                }
+            }
+        }
+        Some("remove") => appt
+            .attendees
+            .retain(|attendee| !handles.contains(&attendee.handle)),
+        Some(command) => return Err(format!("Unknown attendees command: {}", command)),
+    }
+
+    hooks::replace(hooks, apptbook, Action::Edit, id, &handle, appt)?;
+
+    let appt = apptbook.find_id(id).unwrap().appt;
+
+    if appt.attendees.is_empty() {
+        println!("{} has no attendees", appt.description);
+    } else {
+        println!("Attendees: {}", attendees::describe(apptbook, appt));
+    }
+
+    Ok(true)
+}
+
+fn respond_command(args: &[String], apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let (id, name, response) = match args.positional.as_slice() {
+        [id, name, response] => (id, name, Response::parse(response)?),
+        _ => {
+            return Err(format!(
+                "Expected an appointment ID, owner and response\n\n{}",
+                USAGE
+            ))
+        }
+    };
+
+    let id: u64 = id
+        .parse()
+        .map_err(|_| format!("Invalid appointment ID: {}", id))?;
+    let hit = apptbook
+        .find_id(id)
+        .ok_or_else(|| format!("No appointment with ID {}", id))?;
+    let owner = apptbook
+        .find(name)
+        .ok_or_else(|| format!("No owner named {}", name))?;
+
+    let handle = hit.owner.handle.clone();
+    let mut appt = hit.appt.clone();
+
+    match appt
+        .attendees
+        .iter_mut()
+        .find(|attendee| attendee.handle == owner.handle)
+    {
+        Some(attendee) => attendee.response = response,
+        None => {
+            return Err(format!(
+                "{} isn't attending {}",
+                owner.name, appt.description
+            ))
+        }
+    }
+
+    let message = format!("{} {} {}", owner.name, response.name(), appt.description);
+
+    hooks::replace(hooks, apptbook, Action::Edit, id, &handle, appt)?;
+    println!("{}", message);
+
+    Ok(true)
+}
+
+fn status_command(args: &[String], apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let (id, status) = match args.positional.as_slice() {
+        [id] => (id, None),
+        [id, status] => (id, Some(Status::parse(status)?)),
+        _ => return Err(format!("Expected an appointment ID\n\n{}", USAGE)),
+    };
+
+    let id: u64 = id
+        .parse()
+        .map_err(|_| format!("Invalid appointment ID: {}", id))?;
+    let hit = apptbook
+        .find_id(id)
+        .ok_or_else(|| format!("No appointment with ID {}", id))?;
+
+    let status = match status {
+        Some(status) => status,
+        None => {
+            println!("{} is {}", hit.appt.description, hit.appt.status.name());
+            return Ok(false);
+        }
+    };
+
+    // Whether it happened can only be known once it started
+    if (status == Status::Completed || status == Status::NoShow)
+        && hit.appt.start_date_time > Local::now().naive_local()
+    {
+        return Err(format!(
+            "{} hasn't started yet, so it can't be {}",
+            hit.appt.description,
+            status.name()
+        ));
+    }
+
+    let handle = hit.owner.handle.clone();
+    let mut appt = hit.appt.clone();
+    appt.set_status(status)?;
+
+    let message = format!("{} is now {}", appt.description, status.name());
+
+    hooks::replace(hooks, apptbook, Action::Edit, id, &handle, appt)?;
+    println!("{}", message);
+
+    Ok(true)
+}
+
+// Send due reminders once, or keep checking with --watch. The book is reloaded for every check
+// so changes made while watching are picked up
+fn remind_command(args: &[String], reminders: &Reminders) -> Result<bool, String> {
+    let args = Args::parse(args, &["watch"])?;
+
+    let interval = match args.value("interval").map(str::parse) {
+        Some(Ok(interval)) if interval > 0 => interval,
+        Some(_) => return Err(format!("Expected --interval <seconds>\n\n{}", USAGE)),
+        None => 60,
+    };
+
+    let notify = args
+        .value("notify")
+        .map(str::to_string)
+        .or_else(|| reminders.notify.clone());
+    let mail = args
+        .value("mail")
+        .map(str::to_string)
+        .or_else(|| reminders.mail.clone());
+
+    let mut sinks = Vec::new();
+    if let Some(command) = notify {
+        sinks.push(Sink::Command(command));
+    }
+    if let Some(path) = mail {
+        sinks.push(Sink::Mail(path));
+    }
+    if sinks.is_empty() {
+        sinks.push(Sink::Stdout);
+    }
+
+    loop {
+        let apptbook = Book::load(book::STORAGE_FILE);
+        let hits = apptbook.select(|_, _| true);
+        let now = Local::now().naive_local();
+        let mut fired = Fired::load(remind::FIRED_FILE);
+
+        for due in remind::due(&hits, &fired, now) {
+            // A reminder that couldn't be sent anywhere is tried again on the next check
+            let mut sent = false;
+
+            for sink in &sinks {
+                match remind::emit(sink, &due, now) {
+                    Ok(()) => sent = true,
+                    Err(err) => eprintln!("{}", err),
+                }
+            }
+
+            if sent {
+                fired.mark(&due);
+            }
+        }
+
+        fired.prune(&hits, now);
+        fired.save(remind::FIRED_FILE);
+
+        if !args.flag("watch") {
+            return Ok(false);
+        }
+
+        std::thread::sleep(std::time::Duration::from_secs(interval));
+    }
+}
+
+fn owners_command(args: &[String], apptbook: &Book) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    match output_format(&args)? {
+        Some(format) => print!("{}", output::owners(&apptbook.owners, format)),
+        None if apptbook.is_empty() => println!("No appointment owners"),
+        None => {
+            for owner in &apptbook.owners {
+                println!("* {} ({})", owner.name, owner.handle);
+            }
+        }
+    }
+
+    Ok(false)
+}
+
+// Read the --output format for listings, if one was given
+fn output_format(args: &Args) -> Result<Option<output::Format>, String> {
+    args.value("output").map(output::Format::parse).transpose()
+}
+
+fn archive_command(args: &[String], apptbook: &mut Book) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let days = match args.value("days").map(str::parse) {
+        Some(Ok(days)) if (0..=config::MAX_RETENTION_DAYS).contains(&days) => days,
+        _ => return Err(format!("Expected --days <n>\n\n{}", USAGE)),
+    };
+
+    let retention = Retention {
+        archive_after_days: Some(days),
+        purge_after_days: None,
+    };
+
+    let mut archive = archive::load(archive::ARCHIVE_FILE);
+    let outcome = archive::apply(
+        &retention,
+        apptbook,
+        &mut archive,
+        Local::now().naive_local(),
+    );
+
+    if outcome.archived == 0 {
+        println!("No appointments to archive");
+        return Ok(false);
+    }
+
+    archive.save(archive::ARCHIVE_FILE);
+    println!("{} appointment(s) archived successfully", outcome.archived);
+
+    Ok(true)
+}
+
+// Read CSV options shared by export and import
+fn csv_options(args: &Args) -> Result<csv::Options, String> {
+    let mut options = csv::Options::default();
+
+    if let Some(delimiter) = args.value("delimiter") {
+        options.delimiter = match delimiter {
+            "\\t" | "tab" => '\t',
+            _ if delimiter.chars().count() == 1 => delimiter.chars().next().unwrap(),
+            _ => return Err("Invalid delimiter. Must be a single character".to_string()),
+        };
+    }
+
+    if let Some(date_format) = args.value("date-format") {
+        // chrono only reports a bad specifier when formatting, by panicking
+        if chrono::format::StrftimeItems::new(date_format)
+            .any(|item| item == chrono::format::Item::Error)
+        {
+            return Err(format!("Invalid date format: {}", date_format));
+        }
+
+        options.date_format = date_format.to_string();
+    }
+
+    Ok(options)
+}
+
+fn export_command(args: &[String], apptbook: &Book) -> Result<bool, String> {
+    let args = Args::parse(args, &["archived"])?;
+    let filter = Filter::from_args(&args.positional)?;
+
+    let archive;
+    let apptbook = if args.flag("archived") {
+        archive = archive::load(archive::ARCHIVE_FILE);
+        &archive
+    } else {
+        apptbook
+    };
+
+    let hits = filter.select(apptbook);
+    let output = export_output(&hits, &args)?;
+
+    match args.value("file") {
+        Some(path) => {
+            fs::write(path, output).map_err(|err| format!("Failed to write {}: {}", path, err))?;
+            println!("{} appointment(s) exported to {}", hits.len(), path);
+        }
+        None => print!("{}", output),
+    }
+
+    Ok(false)
+}
+
+// Render the hits in the format given by --format
+fn export_output(hits: &[Hit], args: &Args) -> Result<String, String> {
+    let output = match args.value("format") {
+        Some("csv") => csv::export(hits, &csv_options(args)?),
+        Some("html") => {
+            let view = match args.value("view") {
+                Some(view) => html::View::parse(view)?,
+                None => html::View::Both,
+            };
+
+            html::render(
+                hits,
+                args.value("title").unwrap_or("Appointment Book"),
+                view,
+            )
+        }
+        Some("ics") => ical::calendar(hits),
+        Some("jsonl") => jsonl::export(hits),
+        Some("markdown") => agenda::markdown(hits),
+        Some("org") => agenda::org(hits),
+        Some(format) => return Err(format!("Unknown export format: {}", format)),
+        None => return Err(format!("Expected --format <format>\n\n{}", USAGE)),
+    };
+
+    Ok(output)
+}
+
+fn import_command(args: &[String], apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let args = Args::parse(args, &["dry-run"])?;
+
+    let path = match args.positional.as_slice() {
+        [path] => path,
+        _ => return Err(format!("Expected exactly one file to import\n\n{}", USAGE)),
+    };
+
+    // Read from standard input if the path is -
+    let input = if path == "-" {
+        let mut input = String::new();
+        io::stdin()
+            .read_to_string(&mut input)
+            .map_err(|err| format!("Failed to read standard input: {}", err))?;
+        input
+    } else {
+        fs::read_to_string(path).map_err(|err| format!("Failed to read {}: {}", path, err))?
+    };
+
+    let (rows, errors) = match args.value("format") {
+        Some("csv") => {
+            let columns = match args.value("columns") {
+                Some(mapping) => csv::Columns::parse(mapping)?,
+                None => csv::Columns::default(),
+            };
+
+            csv::import(&input, &csv_options(&args)?, &columns)?
+        }
+        Some("jsonl") => jsonl::import(&input),
+        Some(format) => return Err(format!("Unknown import format: {}", format)),
+        None => return Err(format!("Expected --format <format>\n\n{}", USAGE)),
+    };
+
+    for error in &errors {
+        println!("Line {}: {}", error.line, error.message);
+    }
+
+    if !errors.is_empty() {
+        println!();
+    }
+
+    if args.flag("dry-run") {
+        println!(
+            "Dry run: {} row(s) would be imported, {} row(s) failed",
+            rows.len(),
+            errors.len()
+        );
+        return Ok(false);
+    }
+
+    let mut imported = 0;
+    let mut replaced = 0;
+    let mut failed = errors.len();
+
+    // An appointment whose ID is already in the book replaces it, possibly moving it to another
+    // owner. Unknown IDs are dropped so they can't collide with ones handed out later
+    for mut row in rows {
+        let id = row.appt.id;
+        let replacing = id != 0 && apptbook.find_id(id).is_some();
+
+        let result = if replacing {
+            hooks::replace(hooks, apptbook, Action::Import, id, &row.owner, row.appt)
+        } else {
+            row.appt.id = 0;
+            hooks::add(hooks, apptbook, Action::Import, &row.owner, row.appt)
+        };
+
+        match result {
+            Ok(_) => {
+                imported += 1;
+                if replacing {
+                    replaced += 1;
+                }
+            }
+            Err(err) => {
+                println!("{}", err);
+                failed += 1;
+            }
+        }
+    }
+
+    println!(
+        "{} row(s) imported successfully ({} replaced), {} row(s) failed",
+        imported, replaced, failed
+    );
+
+    Ok(imported > 0)
+}
+
+fn delete_command(args: &[String], apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let args = Args::parse(args, &["dry-run", "yes"])?;
+    let filter = Filter::from_args(&args.positional)?;
+
+    if filter.is_empty() {
+        return Err("Expected a filter, e.g. owner:alice before:2026-01-01".to_string());
+    }
+
+    let hits = filter.select(apptbook);
+
+    if hits.is_empty() {
+        println!("No appointments matched the filter");
+        return Ok(false);
+    }
+
+    // Preview what will be removed
+    search::print_hits(&hits);
+    println!();
+
+    // Appointments matched for one of their attendees are declined for them rather than deleted
+    let targets: Vec<(u64, String)> = hits
+        .iter()
+        .map(|hit| (hit.appt.id, hit.owner.handle.clone()))
+        .collect();
+    let count = targets.len();
+
+    if args.flag("dry-run") {
+        println!("Dry run: {} appointment(s) would be deleted", count);
+        return Ok(false);
+    }
+
+    if !args.flag("yes") && !prompt::confirm(&format!("Delete these {} appointment(s)?", count)) {
+        println!("\nCanceled deleting appointments");
+        return Ok(false);
+    }
+
+    let (deleted, declined, refusals) = hooks::delete_for_owners(hooks, apptbook, &targets);
+
+    for refusal in &refusals {
+        println!("{}", refusal);
+    }
+
+    println!("{} appointment(s) deleted successfully", deleted);
+
+    if declined > 0 {
+        println!("{} appointment(s) declined for their attendees", declined);
+    }
+
+    Ok(deleted + declined > 0)
+}
+
+fn report_command(args: &[String], apptbook: &Book) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+    let filter = Filter::from_args(&args.positional)?;
+
+    let today = Local::now().naive_local().date();
+    let first = today.with_day(1).unwrap();
+
+    let from = match args.value("from") {
+        Some(date) => appointment::parse_date(date)?,
+        None => first,
+    };
+    let to = match args.value("to") {
+        Some(date) => appointment::parse_date(date)?,
+        // The day before the first of next month
+        None => (first + chrono::Duration::days(32))
+            .with_day(1)
+            .unwrap()
+            .pred(),
+    };
+
+    if to < from {
+        return Err("Invalid date range. --to cannot be before --from".to_string());
+    }
+
+    let hours = match args.value("hours") {
+        Some(hours) => WorkingHours::parse(hours)?,
+        None => WorkingHours::default(),
+    };
+
+    let report = report::report(&filter.select_participating(apptbook), from, to, hours);
+
+    match args.value("output") {
+        Some("json") => print!("{}", report::json(&report)),
+        Some("text") | None => print!("{}", report::text(&report)),
+        Some(format) => {
+            return Err(format!(
+                "Unknown output format: {}. Must be text or json",
+                format
+            ))
+        }
+    }
+
+    Ok(false)
+}
+
+fn search_command(args: &[String], apptbook: &Book) -> Result<bool, String> {
+    let args = Args::parse(args, &["regex", "archived"])?;
+
+    let text = match args.positional.as_slice() {
+        [text] => text,
+        _ => return Err(format!("Expected exactly one search query\n\n{}", USAGE)),
+    };
+
+    let pattern = if args.flag("regex") {
+        Pattern::regex(text)?
+    } else {
+        Pattern::text(text)
+    };
+
+    let query = Query {
+        pattern,
+        from: args
+            .value("from")
+            .map(appointment::parse_date)
+            .transpose()?,
+        to: args.value("to").map(appointment::parse_date).transpose()?,
+        tags: args.value("tag").map(tags::parse).unwrap_or_default(),
+    };
+
+    let archive;
+    let apptbook = if args.flag("archived") {
+        archive = archive::load(archive::ARCHIVE_FILE);
+        &archive
+    } else {
+        apptbook
+    };
+
+    let hits = search::search(apptbook, &query);
+
+    match output_format(&args)? {
+        Some(format) => print!("{}", output::appointments(&hits, format)),
+        None => search::print_hits(&hits),
+    }
+
+    Ok(false)
+}
+
+// The server reads and writes the storage file itself for every request, so the book loaded for
+// the command isn't used
+fn serve_command(
+    args: &[String],
+    default_port: u16,
+    handler: server::Handler,
+) -> Result<bool, String> {
+    let args = Args::parse(args, &[])?;
+
+    let port = match args.value("port").map(str::parse) {
+        Some(Ok(port)) => port,
+        Some(Err(_)) => return Err("Invalid port. Must be a number from 0 to 65535".to_string()),
+        None => default_port,
+    };
+
+    server::serve(port, handler)?;
+
+    Ok(false)
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn parse_args_with_switches() {
+    let args: Vec<String> = vec!["dentist", "--regex", "--from", "03/01/2020", "extra"]
+        .into_iter()
+        .map(String::from)
+        .collect();
+
+    let parsed = Args::parse(&args, &["regex"]).unwrap();
+
+    assert_eq!(parsed.positional, vec!["dentist", "extra"]);
+    assert!(parsed.flag("regex"));
+    assert_eq!(parsed.value("from"), Some("03/01/2020"));
+    assert_eq!(parsed.value("to"), None);
+    assert!(Args::parse(&["--from".to_string()], &[]).is_err());
+}
+
+#[test]
+fn csv_options_check_date_format() {
+    let args = |date_format: &str| {
+        Args::parse(&["--date-format".to_string(), date_format.to_string()], &[]).unwrap()
+    };
+
+    assert_eq!(
+        csv_options(&args("%Y-%m-%d %H:%M")).unwrap().date_format,
+        "%Y-%m-%d %H:%M"
+    );
+    assert!(csv_options(&args("%Q")).is_err());
+    assert!(csv_options(&args("%Y-%")).is_err());
+}
+
+#[test]
+fn export_as_icalendar() {
+    use crate::appointment::Appointment;
+
+    let mut apptbook = Book::default();
+    let day = NaiveDate::from_ymd(2026, 10, 20);
+    apptbook
+        .add(
+            "Alice",
+            Appointment::new_all_day("Offsite", day, day.succ()).unwrap(),
+        )
+        .unwrap();
+    let hits = Filter::from_args(&[]).unwrap().select(&apptbook);
+
+    let args =
+        |format: &str| Args::parse(&["--format".to_string(), format.to_string()], &[]).unwrap();
+
+    let output = export_output(&hits, &args("ics")).unwrap();
+    assert!(output.starts_with("BEGIN:VCALENDAR\r\n"));
+    assert!(output.contains("SUMMARY:Offsite\r\n"));
+    assert!(output.contains("DTSTART;VALUE=DATE:20261020\r\n"));
+    assert!(output.contains("DTEND;VALUE=DATE:20261022\r\n"));
+    assert!(export_output(&hits, &args("vcf")).is_err());
+}
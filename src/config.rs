@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+
+pub const CONFIG_FILE: &str = "apptbook.config.json";
+
+// The longest retention period, about a century
+pub const MAX_RETENTION_DAYS: i64 = 100 * 366;
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Retention {
+    // Move appointments that ended more than this many days ago into the archive file
+    pub archive_after_days: Option<i64>,
+    // Permanently delete appointments that ended more than this many days ago, whether they
+    // are archived or not
+    pub purge_after_days: Option<i64>,
+}
+
+impl Retention {
+    // Both periods count back from now, so they can't be negative
+    pub fn check(&self) -> Result<(), String> {
+        let periods = [
+            ("archive_after_days", self.archive_after_days),
+            ("purge_after_days", self.purge_after_days),
+        ];
+
+        for (name, days) in periods.iter() {
+            if let Some(days) = days {
+                if *days < 0 || *days > MAX_RETENTION_DAYS {
+                    return Err(format!("{} must be from 0 to {}", name, MAX_RETENTION_DAYS));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Reminders {
+    // Shell command run for each reminder with its title and message, e.g. "notify-send"
+    pub notify: Option<String>,
+    // Mailbox file reminders are appended to, e.g. "/var/mail/alice"
+    pub mail: Option<String>,
+}
+
+// Shell commands run before and after appointments change, reading the change as JSON on
+// standard input. A pre-hook that fails refuses the change
+#[derive(Serialize, Deserialize, Debug, Default, Clone)]
+#[serde(default)]
+pub struct Hooks {
+    pub pre_add: Option<String>,
+    pub post_add: Option<String>,
+    pub pre_edit: Option<String>,
+    pub post_edit: Option<String>,
+    pub pre_delete: Option<String>,
+    pub post_delete: Option<String>,
+    pub pre_import: Option<String>,
+    pub post_import: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Config {
+    pub retention: Retention,
+    pub reminders: Reminders,
+    pub hooks: Hooks,
+}
+
+impl Config {
+    // Read the config file. If it doesn't exist, every setting takes its default
+    pub fn load(path: &str) -> Result<Config, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => Config::from_json(&contents)
+                .map_err(|err| format!("Failed to parse config file: {}", err)),
+            Err(_) => Ok(Config::default()),
+        }
+    }
+
+    pub fn from_json(contents: &str) -> serde_json::Result<Config> {
+        let config: Config = serde_json::from_str(contents)?;
+        config.retention.check().map_err(serde::de::Error::custom)?;
+
+        Ok(config)
+    }
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn config_defaults_missing_settings() {
+    let config = Config::from_json(r#"{"retention": {"archive_after_days": 30}}"#).unwrap();
+
+    assert_eq!(config.retention.archive_after_days, Some(30));
+    assert_eq!(config.retention.purge_after_days, None);
+    assert!(Config::from_json("{}")
+        .unwrap()
+        .retention
+        .archive_after_days
+        .is_none());
+}
+
+#[test]
+fn config_rejects_out_of_range_retention() {
+    assert!(Config::from_json(r#"{"retention": {"purge_after_days": 0}}"#).is_ok());
+    assert!(Config::from_json(r#"{"retention": {"purge_after_days": -60}}"#).is_err());
+    assert!(Config::from_json(r#"{"retention": {"archive_after_days": 99999999999999}}"#).is_err());
+}
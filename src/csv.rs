@@ -0,0 +1,370 @@
+use crate::appointment::{self, Appointment};
+use crate::book::Hit;
+use crate::remind;
+use chrono::Duration;
+
+pub struct Options {
+    pub delimiter: char,
+    // chrono format used for the start and end columns
+    pub date_format: String,
+}
+
+impl Default for Options {
+    fn default() -> Options {
+        Options {
+            delimiter: ',',
+            date_format: appointment::DATE_TIME_FORMAT.to_string(),
+        }
+    }
+}
+
+// Header names of the columns holding each appointment field. The end can be left out when a
+// duration column is given
+pub struct Columns {
+    pub owner: String,
+    pub description: String,
+    pub start: String,
+    pub end: String,
+    pub duration: String,
+}
+
+impl Default for Columns {
+    fn default() -> Columns {
+        Columns {
+            owner: "owner".to_string(),
+            description: "description".to_string(),
+            start: "start".to_string(),
+            end: "end".to_string(),
+            duration: "duration_minutes".to_string(),
+        }
+    }
+}
+
+impl Columns {
+    // Override column names from a list like "owner=Name,start=Begins"
+    pub fn parse(mapping: &str) -> Result<Columns, String> {
+        let mut columns = Columns::default();
+
+        for pair in mapping.split(',') {
+            let (field, header) = match pair.find('=') {
+                Some(index) => (pair[..index].trim(), pair[index + 1..].trim().to_string()),
+                None => return Err(format!("Invalid column mapping: {}", pair)),
+            };
+
+            match field {
+                "owner" => columns.owner = header,
+                "description" => columns.description = header,
+                "start" => columns.start = header,
+                "end" => columns.end = header,
+                "duration_minutes" => columns.duration = header,
+                _ => return Err(format!("Unknown appointment field: {}", field)),
+            }
+        }
+
+        Ok(columns)
+    }
+}
+
+// A row that was imported successfully
+pub struct Row {
+    pub owner: String,
+    pub appt: Appointment,
+}
+
+// A row that failed validation, identified by its line number in the input
+pub struct RowError {
+    pub line: usize,
+    pub message: String,
+}
+
+pub fn export(hits: &[Hit], options: &Options) -> String {
+    let mut output = String::new();
+
+    output.push_str(&record(
+        &["owner", "description", "start", "end", "duration_minutes"],
+        options.delimiter,
+    ));
+
+    for hit in hits {
+        output.push_str(&record(
+            &[
+                &hit.owner.name,
+                &hit.appt.description,
+                &hit.appt
+                    .start_date_time
+                    .format(&options.date_format)
+                    .to_string(),
+                &hit.appt
+                    .end_date_time
+                    .format(&options.date_format)
+                    .to_string(),
+                &hit.appt.duration_minutes().to_string(),
+            ],
+            options.delimiter,
+        ));
+    }
+
+    output
+}
+
+// Parse and validate every row, collecting errors rather than stopping at the first bad row.
+// Fails outright only if the input can't be read as CSV or required columns are missing
+pub fn import(
+    input: &str,
+    options: &Options,
+    columns: &Columns,
+) -> Result<(Vec<Row>, Vec<RowError>), String> {
+    let mut records = parse(input, options.delimiter)?.into_iter();
+
+    let header = match records.next() {
+        Some((_, header)) => header,
+        None => return Ok((Vec::new(), Vec::new())),
+    };
+
+    let find = |name: &str| {
+        header
+            .iter()
+            .position(|column| column.trim().eq_ignore_ascii_case(name))
+    };
+
+    let required = |name: &str| find(name).ok_or_else(|| format!("Missing column: {}", name));
+
+    let owner = required(&columns.owner)?;
+    let description = required(&columns.description)?;
+    let start = required(&columns.start)?;
+    let end = find(&columns.end);
+    let duration = find(&columns.duration);
+
+    if end.is_none() && duration.is_none() {
+        return Err(format!(
+            "Missing column: {} or {}",
+            columns.end, columns.duration
+        ));
+    }
+
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for (line, fields) in records {
+        // Skip blank lines
+        if fields.iter().all(|field| field.trim().is_empty()) {
+            continue;
+        }
+
+        let field = |index: usize| fields.get(index).map(String::as_str).unwrap_or("");
+
+        let row = || -> Result<Row, String> {
+            if field(owner).trim().is_empty() {
+                return Err("Owner cannot be empty".to_string());
+            }
+
+            let start_date_time = appointment::parse_date_time(field(start), &options.date_format)?;
+
+            let end_date_time = match (end, duration) {
+                (Some(end), _) if !field(end).trim().is_empty() => {
+                    appointment::parse_date_time(field(end), &options.date_format)?
+                }
+                (_, Some(duration)) => {
+                    let minutes: i64 = field(duration)
+                        .trim()
+                        .parse()
+                        .map_err(|_| "Invalid duration. Must be a number of minutes".to_string())?;
+
+                    // Durations get the same century limit as reminders
+                    Some(minutes)
+                        .filter(|minutes| (0..=remind::MAX_OFFSET_MINUTES).contains(minutes))
+                        .and_then(|minutes| {
+                            start_date_time.checked_add_signed(Duration::minutes(minutes))
+                        })
+                        .ok_or_else(|| format!("Invalid duration: {}", minutes))?
+                }
+                _ => return Err("Missing end date/time".to_string()),
+            };
+
+            Ok(Row {
+                owner: field(owner).trim().to_string(),
+                appt: Appointment::new(field(description), start_date_time, end_date_time)?,
+            })
+        };
+
+        match row() {
+            Ok(row) => rows.push(row),
+            Err(message) => errors.push(RowError { line, message }),
+        }
+    }
+
+    Ok((rows, errors))
+}
+
+// Format one CSV record, quoting fields that contain the delimiter, quotes or line breaks
+pub fn record(fields: &[&str], delimiter: char) -> String {
+    let quoted: Vec<String> = fields
+        .iter()
+        .map(|field| {
+            if field.contains([delimiter, '"', '\n', '\r']) {
+                format!("\"{}\"", field.replace('"', "\"\""))
+            } else {
+                field.to_string()
+            }
+        })
+        .collect();
+
+    format!("{}\n", quoted.join(&delimiter.to_string()))
+}
+
+// Split CSV input into records of fields, each with the line number it starts on. Quoted fields
+// may contain delimiters, line breaks and doubled quotes
+fn parse(input: &str, delimiter: char) -> Result<Vec<(usize, Vec<String>)>, String> {
+    let mut records = Vec::new();
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if in_quotes {
+            if c == '"' {
+                if chars.peek() == Some(&'"') {
+                    field.push('"');
+                    chars.next();
+                } else {
+                    in_quotes = false;
+                }
+            } else {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        } else if c == '"' && field.is_empty() {
+            in_quotes = true;
+        } else if c == delimiter {
+            fields.push(std::mem::take(&mut field));
+        } else if c == '\n' || c == '\r' {
+            if c == '\r' && chars.peek() == Some(&'\n') {
+                chars.next();
+            }
+
+            fields.push(std::mem::take(&mut field));
+            records.push((record_line, std::mem::take(&mut fields)));
+
+            line += 1;
+            record_line = line;
+        } else {
+            field.push(c);
+        }
+    }
+
+    if in_quotes {
+        return Err(format!(
+            "Invalid CSV. Unterminated quote in record on line {}",
+            record_line
+        ));
+    }
+
+    if !field.is_empty() || !fields.is_empty() {
+        fields.push(field);
+        records.push((record_line, fields));
+    }
+
+    Ok(records)
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn parse_quoted_fields() {
+    let records = parse("a,\"b, \"\"c\"\"\"\r\n\"multi\nline\",d\ne,", ',').unwrap();
+
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records[0],
+        (1, vec!["a".to_string(), "b, \"c\"".to_string()])
+    );
+    assert_eq!(
+        records[1],
+        (2, vec!["multi\nline".to_string(), "d".to_string()])
+    );
+    assert_eq!(records[2], (4, vec!["e".to_string(), "".to_string()]));
+    assert!(parse("\"open", ',').is_err());
+}
+
+#[test]
+fn export_then_import_round_trip() {
+    use crate::book::Book;
+    use chrono::NaiveDateTime;
+
+    let mut apptbook = Book::default();
+    apptbook
+        .add(
+            "Tom",
+            Appointment {
+                description: "Lunch; with \"Lisa\"".to_string(),
+                start_date_time: NaiveDateTime::parse_from_str(
+                    "02/02/2020 11:30",
+                    "%m/%d/%Y %H:%M",
+                )
+                .unwrap(),
+                end_date_time: NaiveDateTime::parse_from_str("02/02/2020 12:15", "%m/%d/%Y %H:%M")
+                    .unwrap(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let options = Options {
+        delimiter: ';',
+        date_format: "%Y-%m-%d %H:%M".to_string(),
+    };
+    let exported = export(&apptbook.select(|_, _| true), &options);
+
+    assert_eq!(
+        exported,
+        "owner;description;start;end;duration_minutes\n\
+         Tom;\"Lunch; with \"\"Lisa\"\"\";2020-02-02 11:30;2020-02-02 12:15;45\n"
+    );
+
+    let (rows, errors) = import(&exported, &options, &Columns::default()).unwrap();
+
+    assert!(errors.is_empty());
+    assert_eq!(rows[0].owner, "Tom");
+    assert_eq!(rows[0].appt.description, "Lunch; with \"Lisa\"");
+    assert_eq!(rows[0].appt.duration_minutes(), 45);
+}
+
+#[test]
+fn import_reports_bad_rows() {
+    let input = "Name,Subject,Begins,duration_minutes\n\
+                 Tom,Lunch,02/02/2020 11:30,45\n\
+                 Tom,Bad date,02/30/2020 11:30,45\n\
+                 \n\
+                 ,No owner,02/02/2020 11:30,45\n\
+                 Tom,Backwards,02/02/2020 11:30,-5\n\
+                 Tom,Bad duration,02/02/2020 11:30,soon\n\
+                 Tom,Forever,02/02/2020 11:30,99999999999999\n";
+
+    let columns = Columns::parse("owner=name, description=subject,start=Begins").unwrap();
+    let (rows, errors) = import(input, &Options::default(), &columns).unwrap();
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].appt.duration_minutes(), 45);
+
+    let lines: Vec<usize> = errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, vec![3, 5, 6, 7, 8]);
+    assert_eq!(
+        errors[0].message,
+        "Invalid date. Required format: mm/dd/yyyy"
+    );
+    assert_eq!(errors[4].message, "Invalid duration: 99999999999999");
+
+    assert!(import(
+        "owner,description\n",
+        &Options::default(),
+        &Columns::default()
+    )
+    .is_err());
+    assert!(Columns::parse("color=Hue").is_err());
+}
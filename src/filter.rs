@@ -0,0 +1,334 @@
+// A small filter expression language for selecting appointments, e.g.
+//
+//     owner:alice after:2026-11-01 before:2026-12-01 duration>60 text:"review"
+//
+// Terms are separated by whitespace and must all match. Values containing spaces can be quoted.
+// Supported terms:
+//
+//     owner:<name>      owner's name or handle, ignoring case. Also matches appointments the
+//                       owner is attending
+//     tag:<tag>         has the tag, ignoring case
+//     status:<status>   tentative, confirmed, completed, cancelled or no-show
+//     after:<date>      starts on or after the date
+//     before:<date>     starts before the date
+//     on:<date>         overlaps the date
+//     duration<op><n>   length in minutes compared with <, <=, >, >=, or =
+//     text:<text>       description contains the text, ignoring case
+//     regex:<pattern>   description matches the regex, ignoring case
+//     <text>            shorthand for text:<text>
+//
+// Dates are accepted as yyyy-mm-dd or mm/dd/yyyy
+
+use crate::appointment::{self, Appointment, Status};
+use crate::book::{Book, Hit};
+use crate::owner::Owner;
+use crate::search::Pattern;
+use crate::tags;
+use chrono::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Comparison {
+    Less,
+    LessOrEqual,
+    Greater,
+    GreaterOrEqual,
+    Equal,
+}
+
+impl Comparison {
+    fn compare(self, left: i64, right: i64) -> bool {
+        match self {
+            Comparison::Less => left < right,
+            Comparison::LessOrEqual => left <= right,
+            Comparison::Greater => left > right,
+            Comparison::GreaterOrEqual => left >= right,
+            Comparison::Equal => left == right,
+        }
+    }
+}
+
+enum Term {
+    Owner(String),
+    Tag(String),
+    Status(Status),
+    After(NaiveDateTime),
+    Before(NaiveDateTime),
+    On(NaiveDate),
+    Duration(Comparison, i64),
+    Text(Pattern),
+}
+
+impl Term {
+    fn matches(&self, owner: &Owner, appt: &Appointment) -> bool {
+        match self {
+            Term::Owner(name) => owner.matches(name),
+            Term::Tag(tag) => appt.tags.contains(tag),
+            Term::Status(status) => appt.status == *status,
+            Term::After(date_time) => appt.start_date_time >= *date_time,
+            Term::Before(date_time) => appt.start_date_time < *date_time,
+            Term::On(date) => appt.occurs_on(*date),
+            Term::Duration(comparison, minutes) => {
+                comparison.compare(appt.duration_minutes(), *minutes)
+            }
+            Term::Text(pattern) => pattern.is_match(&appt.description),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Filter {
+    terms: Vec<Term>,
+}
+
+impl Filter {
+    pub fn parse(input: &str) -> Result<Filter, String> {
+        let mut terms = Vec::new();
+
+        for token in tokenize(input)? {
+            terms.push(parse_term(&token)?);
+        }
+
+        Ok(Filter { terms })
+    }
+
+    // Parse a filter given as separate command-line arguments
+    pub fn from_args(args: &[String]) -> Result<Filter, String> {
+        let quoted: Vec<String> = args
+            .iter()
+            .map(|arg| {
+                if arg.contains(char::is_whitespace) {
+                    // Keep a shell-quoted value together, e.g. text:"weekly review"
+                    match arg.find(':') {
+                        Some(index) => format!("{}\"{}\"", &arg[..=index], &arg[index + 1..]),
+                        None => format!("\"{}\"", arg),
+                    }
+                } else {
+                    arg.clone()
+                }
+            })
+            .collect();
+
+        Filter::parse(&quoted.join(" "))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn matches(&self, owner: &Owner, appt: &Appointment) -> bool {
+        self.terms.iter().all(|term| term.matches(owner, appt))
+    }
+
+    // Collect every appointment in the book matching the filter. With an owner term, that includes
+    // appointments the owner is attending, as theirs
+    pub fn select<'a>(&self, apptbook: &'a Book) -> Vec<Hit<'a>> {
+        let has_owner = self.terms.iter().any(|term| matches!(term, Term::Owner(_)));
+
+        if has_owner {
+            self.select_participating(apptbook)
+        } else {
+            apptbook.select(|owner, appt| self.matches(owner, appt))
+        }
+    }
+
+    // Collect every appointment matching the filter for each owner taking part in it
+    pub fn select_participating<'a>(&self, apptbook: &'a Book) -> Vec<Hit<'a>> {
+        apptbook.select_participating(|owner, appt| self.matches(owner, appt))
+    }
+}
+
+// Split on whitespace, keeping double-quoted sections together (without the quotes)
+fn tokenize(input: &str) -> Result<Vec<String>, String> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut has_token = false;
+
+    for c in input.chars() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+            has_token = true;
+        } else if c.is_whitespace() && !in_quotes {
+            if has_token {
+                tokens.push(current.clone());
+                current.clear();
+                has_token = false;
+            }
+        } else {
+            current.push(c);
+            has_token = true;
+        }
+    }
+
+    if in_quotes {
+        return Err("Invalid filter. Unterminated quote".to_string());
+    }
+
+    if has_token {
+        tokens.push(current);
+    }
+
+    Ok(tokens)
+}
+
+fn parse_term(token: &str) -> Result<Term, String> {
+    if let Some(rest) = token.strip_prefix("duration") {
+        let (comparison, value) = if let Some(value) = rest.strip_prefix("<=") {
+            (Comparison::LessOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix(">=") {
+            (Comparison::GreaterOrEqual, value)
+        } else if let Some(value) = rest.strip_prefix('<') {
+            (Comparison::Less, value)
+        } else if let Some(value) = rest.strip_prefix('>') {
+            (Comparison::Greater, value)
+        } else if let Some(value) = rest.strip_prefix('=').or_else(|| rest.strip_prefix(':')) {
+            (Comparison::Equal, value)
+        } else {
+            return Ok(Term::Text(Pattern::text(token)));
+        };
+
+        return match value.parse() {
+            Ok(minutes) => Ok(Term::Duration(comparison, minutes)),
+            Err(_) => Err(format!(
+                "Invalid filter. Duration must be a number of minutes: {}",
+                token
+            )),
+        };
+    }
+
+    let (key, value) = match token.find(':') {
+        Some(index) => (&token[..index], &token[index + 1..]),
+        None => return Ok(Term::Text(Pattern::text(token))),
+    };
+
+    match key {
+        "owner" => Ok(Term::Owner(value.to_string())),
+        "tag" => Ok(Term::Tag(tags::normalize(value))),
+        "status" => Ok(Term::Status(Status::parse(value)?)),
+        "after" => Ok(Term::After(parse_date(value)?.and_hms(0, 0, 0))),
+        "before" => Ok(Term::Before(parse_date(value)?.and_hms(0, 0, 0))),
+        "on" => Ok(Term::On(parse_date(value)?)),
+        "text" => Ok(Term::Text(Pattern::text(value))),
+        "regex" => Ok(Term::Text(Pattern::regex(value)?)),
+        _ => Err(format!("Invalid filter. Unknown term: {}", key)),
+    }
+}
+
+// Parse a yyyy-mm-dd or mm/dd/yyyy date
+fn parse_date(input: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(input, "%Y-%m-%d")
+        .or_else(|_| appointment::parse_date(input))
+        .map_err(|_| {
+            format!(
+                "Invalid filter. Dates must be yyyy-mm-dd or mm/dd/yyyy: {}",
+                input
+            )
+        })
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn tokenize_quoted_values() {
+    assert_eq!(
+        tokenize(r#"owner:alice text:"weekly review"  "a b""#).unwrap(),
+        vec!["owner:alice", "text:weekly review", "a b"]
+    );
+    assert!(tokenize(r#"text:"open"#).is_err());
+}
+
+#[test]
+fn filter_combines_terms() {
+    let mut apptbook = Book::sample(&[
+        (
+            "Alice",
+            "Code review",
+            "11/03/2026 09:00",
+            "11/03/2026 10:30",
+        ),
+        (
+            "Alice",
+            "Quick review",
+            "11/04/2026 09:00",
+            "11/04/2026 09:15",
+        ),
+        (
+            "Alice",
+            "Design review",
+            "12/01/2026 09:00",
+            "12/01/2026 11:00",
+        ),
+        ("Bob", "Code review", "11/03/2026 09:00", "11/03/2026 10:30"),
+    ]);
+
+    let filter = Filter::parse(
+        r#"owner:ALICE after:2026-11-01 before:12/01/2026 duration>60 text:"review""#,
+    )
+    .unwrap();
+    let hits = filter.select(&apptbook);
+
+    assert_eq!(hits.len(), 1);
+    assert_eq!(hits[0].owner.name, "Alice");
+    assert_eq!(hits[0].appt.description, "Code review");
+
+    let filter = Filter::parse("on:2026-11-04 regex:^quick").unwrap();
+    assert_eq!(filter.select(&apptbook).len(), 1);
+
+    apptbook.owners[1].appointments[0].tags = vec!["client".to_string()];
+    let filter = Filter::parse("tag:Client review").unwrap();
+    assert_eq!(filter.select(&apptbook)[0].owner.name, "Bob");
+
+    apptbook.owners[0].appointments[1].status = appointment::Status::Cancelled;
+    let filter = Filter::parse("status:cancelled").unwrap();
+    assert_eq!(filter.select(&apptbook)[0].appt.description, "Quick review");
+    assert!(Filter::parse("status:maybe").is_err());
+
+    // An all-day appointment ends at midnight, but doesn't occur on the next day
+    apptbook
+        .add(
+            "Bob",
+            Appointment::new_all_day(
+                "Holiday",
+                NaiveDate::from_ymd(2026, 11, 26),
+                NaiveDate::from_ymd(2026, 11, 26),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    assert_eq!(
+        Filter::parse("on:2026-11-26")
+            .unwrap()
+            .select(&apptbook)
+            .len(),
+        1
+    );
+    assert!(Filter::parse("on:2026-11-27")
+        .unwrap()
+        .select(&apptbook)
+        .is_empty());
+    apptbook.remove_where(|_, appt| appt.all_day);
+
+    assert_eq!(Filter::parse("").unwrap().select(&apptbook).len(), 4);
+}
+
+#[test]
+fn filter_from_args_keeps_spaces() {
+    let args = vec!["owner:bob".to_string(), "text:code review".to_string()];
+    let filter = Filter::from_args(&args).unwrap();
+
+    let bob = Owner::new("Bob");
+    let review = Appointment::sample("Code review", "11/03/2026 09:00", "11/03/2026 10:30");
+    let code = Appointment::sample("Code", "11/03/2026 09:00", "11/03/2026 10:30");
+
+    assert!(filter.matches(&bob, &review));
+    assert!(!filter.matches(&bob, &code));
+}
+
+#[test]
+fn filter_parse_errors() {
+    assert!(Filter::parse("duration>abc").is_err());
+    assert!(Filter::parse("after:13/45/2026").is_err());
+    assert!(Filter::parse("color:red").is_err());
+    assert!(Filter::parse("regex:(").is_err());
+}
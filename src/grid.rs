@@ -0,0 +1,530 @@
+use crate::appointment::{Appointment, Status};
+use crate::book::Hit;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::fmt::Write;
+
+// Appointment lines shown in each day of the month grid before the rest are summarized
+const MONTH_LINES: usize = 3;
+
+// Hours always shown in the week grid, widened to fit earlier or later appointments
+const DAY_START: u32 = 8;
+const DAY_END: u32 = 18;
+
+pub struct Options {
+    // Total width of the grid in columns
+    pub width: usize,
+    // Marked in the grid if it's shown
+    pub today: NaiveDate,
+}
+
+// Draw the week containing the day as a grid of days by hours. All-day appointments and those
+// spanning several days are drawn as bars above the hours
+pub fn week(hits: &[Hit], day: NaiveDate, options: &Options) -> String {
+    let monday = day - Duration::days(day.weekday().num_days_from_monday() as i64);
+    let days: Vec<NaiveDate> = (0..7).map(|i| monday + Duration::days(i)).collect();
+    let sunday = days[6];
+
+    // Time label, then seven cells separated by borders
+    let cell = (options.width.saturating_sub(6 + 8) / 7).max(5);
+    let letters = owner_letters(hits);
+    let label = |text: &str| format!("{:6}", text);
+
+    let in_week: Vec<&Hit> = hits
+        .iter()
+        .filter(|hit| hit.appt.start_date_time.date() <= sunday && hit.appt.last_day() >= monday)
+        .collect();
+    let (spans, singles): (Vec<&Hit>, Vec<&Hit>) = in_week.into_iter().partition(|hit| {
+        hit.appt.all_day || hit.appt.start_date_time.date() != hit.appt.last_day()
+    });
+
+    let mut out = String::new();
+    writeln!(out, "Week of {}", monday.format("%m/%d/%Y")).unwrap();
+
+    let headers: Vec<String> = days
+        .iter()
+        .map(|day| {
+            // Narrow cells leave out the month, which is given by the title
+            let header = if cell >= 11 {
+                day.format("%a %m/%d").to_string()
+            } else {
+                day.format("%a %d").to_string()
+            };
+
+            if *day == options.today {
+                format!("*{}*", header)
+            } else {
+                header
+            }
+        })
+        .collect();
+
+    out.push_str(&border(&label(""), '┌', '┬', '┐', cell));
+    out.push_str(&row(&label(""), &headers, cell));
+
+    // Bars for all-day and multi-day appointments, packed into as few lanes as possible
+    if !spans.is_empty() {
+        out.push_str(&border(&label(""), '├', '┼', '┤', cell));
+
+        let mut lanes: Vec<Vec<(usize, usize, &Hit)>> = Vec::new();
+
+        for hit in spans {
+            let first = (hit.appt.start_date_time.date().max(monday) - monday).num_days() as usize;
+            let last = (hit.appt.last_day().min(sunday) - monday).num_days() as usize;
+
+            let lane = lanes.iter().position(|lane| {
+                lane.iter()
+                    .all(|&(other_first, other_last, _)| last < other_first || first > other_last)
+            });
+
+            match lane {
+                Some(index) => lanes[index].push((first, last, hit)),
+                None => lanes.push(vec![(first, last, hit)]),
+            }
+        }
+
+        for mut lane in lanes {
+            lane.sort_by_key(|&(first, _, _)| first);
+            out.push_str(&label(""));
+            out.push('│');
+
+            let mut column = 0;
+
+            for (first, last, hit) in lane {
+                for _ in column..first {
+                    out.push_str(&fit("", cell));
+                    out.push('│');
+                }
+
+                // The bar covers the cells and the borders between them
+                let length = (last - first + 1) * (cell + 1) - 1;
+                let continues_before = hit.appt.start_date_time.date() < monday;
+                let continues_after = hit.appt.last_day() > sunday;
+
+                let mut bar = String::new();
+                if continues_before {
+                    bar.push('<');
+                }
+                bar.push_str(&describe(hit, &letters));
+                bar.push(' ');
+
+                let arrow = if continues_after { ">" } else { "" };
+                let fill = length.saturating_sub(bar.chars().count() + arrow.len());
+                bar.extend(std::iter::repeat_n('═', fill));
+                bar.push_str(arrow);
+
+                out.push_str(&fit(&bar, length));
+                out.push('│');
+                column = last + 1;
+            }
+
+            for _ in column..7 {
+                out.push_str(&fit("", cell));
+                out.push('│');
+            }
+
+            out.push('\n');
+        }
+    }
+
+    out.push_str(&border(&label(""), '├', '┼', '┤', cell));
+
+    // Hours from the earliest start to the latest end, covering at least the working day
+    let start_hour = singles
+        .iter()
+        .map(|hit| hit.appt.start_date_time.hour())
+        .min()
+        .unwrap_or(DAY_START)
+        .min(DAY_START);
+    let end_hour = singles
+        .iter()
+        .map(|hit| {
+            let end = hit.appt.end_date_time;
+            if end.date() > hit.appt.start_date_time.date() {
+                24
+            } else if end.minute() > 0 {
+                end.hour() + 1
+            } else {
+                end.hour()
+            }
+        })
+        .max()
+        .unwrap_or(DAY_END)
+        .max(DAY_END);
+
+    for hour in start_hour..end_hour {
+        let cells: Vec<String> = days
+            .iter()
+            .map(|day| {
+                let slot_start = day.and_hms(hour, 0, 0);
+                let slot_end = slot_start + Duration::hours(1);
+                let in_slot: Vec<&&Hit> = singles
+                    .iter()
+                    .filter(|hit| overlaps(hit.appt, slot_start, slot_end))
+                    .collect();
+                let starting: Vec<&&Hit> = in_slot
+                    .iter()
+                    .filter(|hit| hit.appt.start_date_time >= slot_start)
+                    .cloned()
+                    .collect();
+
+                match (starting.first(), in_slot.is_empty()) {
+                    (Some(hit), _) if in_slot.len() > 1 => {
+                        format!("{} +{}", describe(hit, &letters), in_slot.len() - 1)
+                    }
+                    (Some(hit), _) => describe(hit, &letters),
+                    // Continuing from an earlier hour
+                    (None, false) => "░".repeat(cell),
+                    (None, true) => String::new(),
+                }
+            })
+            .collect();
+
+        out.push_str(&row(&label(&format!("{:02}:00", hour)), &cells, cell));
+    }
+
+    out.push_str(&border(&label(""), '└', '┴', '┘', cell));
+    out.push_str(&legend(&letters));
+
+    out
+}
+
+// Draw the month containing the day as a grid of weeks, listing each day's appointments.
+// All-day appointments have no time, and those spanning several days are continued on each day
+// with a ">"
+pub fn month(hits: &[Hit], day: NaiveDate, options: &Options) -> String {
+    let first = day.with_day(1).unwrap();
+    let next_month = if first.month() == 12 {
+        NaiveDate::from_ymd(first.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd(first.year(), first.month() + 1, 1)
+    };
+    let monday = first - Duration::days(first.weekday().num_days_from_monday() as i64);
+
+    let cell = (options.width.saturating_sub(8) / 7).max(5);
+    let letters = owner_letters(hits);
+
+    let mut out = String::new();
+    writeln!(out, "{}", first.format("%B %Y")).unwrap();
+
+    let headers: Vec<String> = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"]
+        .iter()
+        .map(|name| name.to_string())
+        .collect();
+
+    out.push_str(&border("", '┌', '┬', '┐', cell));
+    out.push_str(&row("", &headers, cell));
+
+    let mut week_start = monday;
+
+    while week_start < next_month {
+        out.push_str(&border("", '├', '┼', '┤', cell));
+
+        let days: Vec<NaiveDate> = (0..7).map(|i| week_start + Duration::days(i)).collect();
+        let in_month = |day: &NaiveDate| day.month() == first.month();
+
+        // Each day's lines: its number, then its appointments
+        let cells: Vec<Vec<String>> = days
+            .iter()
+            .map(|day| {
+                if !in_month(day) {
+                    return Vec::new();
+                }
+
+                let number = if *day == options.today {
+                    format!("[{}]", day.day())
+                } else {
+                    format!(" {}", day.day())
+                };
+
+                let mut lines = vec![number];
+                let mut appts: Vec<&Hit> =
+                    hits.iter().filter(|hit| hit.appt.occurs_on(*day)).collect();
+                appts.sort_by_key(|hit| hit.appt.start_date_time);
+
+                for (index, hit) in appts.iter().enumerate() {
+                    if index + 1 == MONTH_LINES && appts.len() > MONTH_LINES {
+                        lines.push(format!("+{} more", appts.len() - index));
+                        break;
+                    }
+
+                    if hit.appt.start_date_time.date() != *day {
+                        lines.push(format!("> {}", describe(hit, &letters)));
+                    } else if hit.appt.all_day {
+                        lines.push(describe(hit, &letters));
+                    } else {
+                        lines.push(format!(
+                            "{} {}",
+                            hit.appt.start_date_time.format("%H:%M"),
+                            describe(hit, &letters)
+                        ));
+                    }
+                }
+
+                lines
+            })
+            .collect();
+
+        for line in 0..=MONTH_LINES {
+            let texts: Vec<String> = cells
+                .iter()
+                .map(|lines| lines.get(line).cloned().unwrap_or_default())
+                .collect();
+            out.push_str(&row("", &texts, cell));
+        }
+
+        week_start += Duration::days(7);
+    }
+
+    out.push_str(&border("", '└', '┴', '┘', cell));
+    out.push_str(&legend(&letters));
+
+    out
+}
+
+// With several owners, each is given a letter to tell their appointments apart
+fn owner_letters(hits: &[Hit]) -> Vec<(String, String)> {
+    let mut owners: Vec<(String, String)> = Vec::new();
+
+    for hit in hits {
+        if !owners.iter().any(|(handle, _)| *handle == hit.owner.handle) {
+            owners.push((hit.owner.handle.clone(), hit.owner.name.clone()));
+        }
+    }
+
+    if owners.len() < 2 {
+        return Vec::new();
+    }
+
+    owners
+}
+
+fn letter(index: usize) -> char {
+    (b'A' + (index % 26) as u8) as char
+}
+
+// The appointment's description, prefixed with its owner's letter if there is one. Tentative
+// appointments are marked with ? and cancelled ones with ✗
+fn describe(hit: &Hit, letters: &[(String, String)]) -> String {
+    let description = match hit.appt.status {
+        Status::Tentative => format!("? {}", hit.appt.description.replace('\n', " ")),
+        Status::Cancelled => format!("✗ {}", hit.appt.description.replace('\n', " ")),
+        _ => hit.appt.description.replace('\n', " "),
+    };
+
+    match letters
+        .iter()
+        .position(|(handle, _)| *handle == hit.owner.handle)
+    {
+        Some(index) => format!("{} {}", letter(index), description),
+        None => description,
+    }
+}
+
+fn legend(letters: &[(String, String)]) -> String {
+    if letters.is_empty() {
+        return String::new();
+    }
+
+    let entries: Vec<String> = letters
+        .iter()
+        .enumerate()
+        .map(|(index, (_, name))| format!("{} = {}", letter(index), name))
+        .collect();
+
+    format!("{}\n", entries.join("  "))
+}
+
+// Whether the appointment falls in the slot. An instantaneous appointment does if it's at or
+// after the slot's start
+fn overlaps(appt: &Appointment, start: NaiveDateTime, end: NaiveDateTime) -> bool {
+    appt.start_date_time < end && (appt.end_date_time > start || appt.start_date_time >= start)
+}
+
+fn border(prefix: &str, left: char, middle: char, right: char, cell: usize) -> String {
+    let cells = vec!["─".repeat(cell); 7];
+    format!(
+        "{}{}{}{}\n",
+        prefix,
+        left,
+        cells.join(&middle.to_string()),
+        right
+    )
+}
+
+fn row(prefix: &str, cells: &[String], cell: usize) -> String {
+    let cells: Vec<String> = cells.iter().map(|text| fit(text, cell)).collect();
+    format!("{}│{}│\n", prefix, cells.join("│"))
+}
+
+// Pad or cut text to exactly the width
+pub fn fit(text: &str, width: usize) -> String {
+    let mut fitted: String = text.chars().take(width).collect();
+    let length = fitted.chars().count();
+    fitted.extend(std::iter::repeat_n(' ', width - length));
+    fitted
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn sample_book() -> crate::book::Book {
+    crate::book::Book::sample(&[
+        ("Alice", "Review", "10/19/2026 09:00", "10/19/2026 10:30"),
+        ("Alice", "Retreat", "10/21/2026 09:00", "10/23/2026 17:00"),
+        ("Alice", "Trip", "10/24/2026 09:00", "10/27/2026 17:00"),
+        ("Bob", "Standup", "10/19/2026 08:00", "10/19/2026 08:15"),
+        ("Bob", "Lunch", "10/19/2026 12:00", "10/19/2026 13:00"),
+        ("Bob", "Call", "10/19/2026 12:30", "10/19/2026 12:45"),
+    ])
+}
+
+#[test]
+fn week_grid() {
+    let apptbook = sample_book();
+    let alice = apptbook.select(|owner, _| owner.handle == "alice");
+    let options = Options {
+        width: 80,
+        today: NaiveDate::from_ymd(2026, 10, 20),
+    };
+
+    let grid = week(&alice, NaiveDate::from_ymd(2026, 10, 22), &options);
+    let lines: Vec<&str> = grid.lines().collect();
+
+    assert_eq!(lines[0], "Week of 10/19/2026");
+    assert_eq!(
+        lines[2],
+        "      │Mon 19   │*Tue 20* │Wed 21   │Thu 22   │Fri 23   │Sat 24   │Sun 25   │"
+    );
+    assert_eq!(
+        lines[4],
+        "      │         │         │Retreat ═════════════════════│Trip ═════════════>│"
+    );
+    assert_eq!(
+        lines[7],
+        "09:00 │Review   │         │         │         │         │         │         │"
+    );
+    assert_eq!(
+        lines[8],
+        "10:00 │░░░░░░░░░│         │         │         │         │         │         │"
+    );
+    assert!(lines.iter().all(|line| line.chars().count() <= 80));
+    assert!(lines.last().unwrap().starts_with("      └"));
+}
+
+#[test]
+fn week_grid_several_owners() {
+    let apptbook = sample_book();
+    let options = Options {
+        width: 100,
+        today: NaiveDate::from_ymd(2026, 1, 1),
+    };
+
+    let grid = week(
+        &apptbook.select(|_, _| true),
+        NaiveDate::from_ymd(2026, 10, 19),
+        &options,
+    );
+
+    assert!(grid.contains("08:00 │B Standup   │"));
+    assert!(grid.contains("12:00 │B Lunch +1  │"));
+    assert!(grid.contains("│A Review    │"));
+    assert!(grid.ends_with("A = Alice  B = Bob\n"));
+    assert!(!grid.contains('*'));
+}
+
+#[test]
+fn month_grid() {
+    let apptbook = sample_book();
+    let options = Options {
+        width: 80,
+        today: NaiveDate::from_ymd(2026, 10, 19),
+    };
+
+    let grid = month(
+        &apptbook.select(|_, _| true),
+        NaiveDate::from_ymd(2026, 10, 5),
+        &options,
+    );
+    let lines: Vec<&str> = grid.lines().collect();
+
+    assert_eq!(lines[0], "October 2026");
+    assert_eq!(
+        lines[4],
+        "│          │          │          │ 1        │ 2        │ 3        │ 4        │"
+    );
+
+    // The week of the 19th, with the multi-day appointments continued
+    let week: Vec<&str> = lines[19..23].to_vec();
+    assert_eq!(
+        week,
+        vec![
+            "│[19]      │ 20       │ 21       │ 22       │ 23       │ 24       │ 25       │",
+            "│08:00 B St│          │09:00 A Re│> A Retrea│> A Retrea│09:00 A Tr│> A Trip  │",
+            "│09:00 A Re│          │          │          │          │          │          │",
+            "│+2 more   │          │          │          │          │          │          │",
+        ]
+    );
+    assert!(lines[25].starts_with("│> A Trip  │> A Trip  │          │"));
+}
+
+#[test]
+fn narrow_grids_keep_cells_readable() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+    let day = NaiveDate::from_ymd(2026, 10, 19);
+
+    for width in &[0, 20, 40] {
+        let options = Options {
+            width: *width,
+            today: day,
+        };
+
+        // Seven cells of five columns, the borders, and the time labels for weeks
+        let week = week(&hits, day, &options);
+        assert!(week.contains("08:00 │B Sta│"));
+        assert!(week
+            .lines()
+            .filter(|line| line.starts_with("      │"))
+            .all(|line| line.chars().count() == 6 + 8 + 7 * 5));
+
+        let month = month(&hits, day, &options);
+        assert!(month
+            .lines()
+            .filter(|line| line.starts_with('│'))
+            .all(|line| line.chars().count() == 8 + 7 * 5));
+    }
+}
+
+#[test]
+fn all_day_bars() {
+    let mut apptbook = crate::book::Book::default();
+    apptbook
+        .add(
+            "Alice",
+            Appointment::new_all_day(
+                "Holiday",
+                NaiveDate::from_ymd(2026, 10, 20),
+                NaiveDate::from_ymd(2026, 10, 20),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let options = Options {
+        width: 80,
+        today: NaiveDate::from_ymd(2026, 1, 1),
+    };
+    let hits = apptbook.select(|_, _| true);
+
+    let grid = week(&hits, NaiveDate::from_ymd(2026, 10, 19), &options);
+    let lines: Vec<&str> = grid.lines().collect();
+    assert_eq!(
+        lines[4],
+        "      │         │Holiday ═│         │         │         │         │         │"
+    );
+    assert!(!lines[5..].iter().any(|line| line.contains("Holiday")));
+
+    let grid = month(&hits, NaiveDate::from_ymd(2026, 10, 1), &options);
+    assert!(grid.contains("│Holiday   │"));
+}
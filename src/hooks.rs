@@ -0,0 +1,415 @@
+use crate::appointment::Appointment;
+use crate::attendees::Response;
+use crate::book::{self, Book};
+use crate::config::Hooks;
+use crate::owner::Owner;
+use serde::Serialize;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+// Changes hooks can run around. Imports are reported separately from adds and edits so a hook
+// can treat bulk changes differently
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Action {
+    Add,
+    Edit,
+    Delete,
+    Import,
+}
+
+impl Action {
+    fn name(self) -> &'static str {
+        match self {
+            Action::Add => "add",
+            Action::Edit => "edit",
+            Action::Delete => "delete",
+            Action::Import => "import",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OwnerRecord<'a> {
+    name: &'a str,
+    handle: &'a str,
+}
+
+// What a hook reads on standard input. Edits also include the appointment as it was
+#[derive(Serialize)]
+struct Payload<'a> {
+    action: &'static str,
+    owner: OwnerRecord<'a>,
+    appointment: &'a Appointment,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous: Option<&'a Appointment>,
+}
+
+// Add an appointment, running the add or import hooks around it. Returns its ID, or the pre-hook's
+// message if it refused the change
+pub fn add(
+    hooks: &Hooks,
+    apptbook: &mut Book,
+    action: Action,
+    owner: &str,
+    appt: Appointment,
+) -> Result<u64, String> {
+    book::check_owners(owner, &appt)?;
+
+    let new_owner = Owner::new(owner);
+    let known = apptbook.find(owner).unwrap_or(&new_owner);
+    before(hooks, action, known, &appt, None)?;
+
+    let id = apptbook.add(owner, appt)?;
+    after_id(hooks, apptbook, action, id, None);
+
+    Ok(id)
+}
+
+// Replace the appointment with the ID, which keeps it, running the edit or import hooks around it.
+// The owner may be a different one
+pub fn replace(
+    hooks: &Hooks,
+    apptbook: &mut Book,
+    action: Action,
+    id: u64,
+    owner: &str,
+    mut appt: Appointment,
+) -> Result<u64, String> {
+    let previous = match apptbook.find_id(id) {
+        Some(hit) => hit.appt.clone(),
+        None => return Err("No such appointment".to_string()),
+    };
+
+    appt.id = id;
+    book::check_owners(owner, &appt)?;
+
+    let new_owner = Owner::new(owner);
+    let known = apptbook.find(owner).unwrap_or(&new_owner);
+    before(hooks, action, known, &appt, Some(&previous))?;
+
+    apptbook.remove_id(id);
+    apptbook.add(owner, appt)?;
+    after_id(hooks, apptbook, action, id, Some(&previous));
+
+    Ok(id)
+}
+
+// Delete the appointment with the ID, running the delete hooks around it
+pub fn delete(hooks: &Hooks, apptbook: &mut Book, id: u64) -> Result<(), String> {
+    let hit = match apptbook.find_id(id) {
+        Some(hit) => hit,
+        None => return Err("No such appointment".to_string()),
+    };
+
+    before(hooks, Action::Delete, hit.owner, hit.appt, None)?;
+
+    let owner = hit.owner.clone();
+    let (_, appt) = apptbook.remove_id(id).unwrap();
+    after(hooks, Action::Delete, &owner, &appt, None);
+
+    Ok(())
+}
+
+// Delete each of the appointments, skipping those a pre-hook refuses. Returns how many were
+// deleted and the messages of the refusals
+pub fn delete_ids(hooks: &Hooks, apptbook: &mut Book, ids: &[u64]) -> (usize, Vec<String>) {
+    let mut deleted = 0;
+    let mut refusals = Vec::new();
+
+    for &id in ids {
+        match delete(hooks, apptbook, id) {
+            Ok(()) => deleted += 1,
+            Err(message) => refusals.push(message),
+        }
+    }
+
+    (deleted, refusals)
+}
+
+// Decline the appointment with the ID for one of its attendees, which is an edit the hooks can
+// refuse. The appointment stays with its organizer and other attendees
+pub fn decline(hooks: &Hooks, apptbook: &mut Book, id: u64, handle: &str) -> Result<(), String> {
+    let hit = match apptbook.find_id(id) {
+        Some(hit) => hit,
+        None => return Err("No such appointment".to_string()),
+    };
+
+    let organizer = hit.owner.handle.clone();
+    let mut appt = hit.appt.clone();
+
+    for attendee in &mut appt.attendees {
+        if attendee.handle == handle {
+            attendee.response = Response::Declined;
+        }
+    }
+
+    replace(hooks, apptbook, Action::Edit, id, &organizer, appt)?;
+
+    Ok(())
+}
+
+// Remove each appointment from the schedule of the owner it was selected for, given as (ID,
+// owner handle) pairs. Appointments the owner organized are deleted, and those they're only
+// attending are declined for them. Returns how many were deleted, how many declined, and the
+// messages of the refusals
+pub fn delete_for_owners(
+    hooks: &Hooks,
+    apptbook: &mut Book,
+    targets: &[(u64, String)],
+) -> (usize, usize, Vec<String>) {
+    let (mut deleted, mut declined) = (0, 0);
+    let mut refusals = Vec::new();
+
+    for (id, handle) in targets {
+        let organizer = match apptbook.find_id(*id) {
+            Some(hit) => hit.owner.handle.clone(),
+            // Already deleted for another owner
+            None => continue,
+        };
+
+        let result = if &organizer == handle {
+            delete(hooks, apptbook, *id).map(|()| deleted += 1)
+        } else {
+            decline(hooks, apptbook, *id, handle).map(|()| declined += 1)
+        };
+
+        if let Err(message) = result {
+            refusals.push(message);
+        }
+    }
+
+    (deleted, declined, refusals)
+}
+
+fn before(
+    hooks: &Hooks,
+    action: Action,
+    owner: &Owner,
+    appt: &Appointment,
+    previous: Option<&Appointment>,
+) -> Result<(), String> {
+    let command = match action {
+        Action::Add => &hooks.pre_add,
+        Action::Edit => &hooks.pre_edit,
+        Action::Delete => &hooks.pre_delete,
+        Action::Import => &hooks.pre_import,
+    };
+
+    match command {
+        Some(command) => run(command, "pre", action, owner, appt, previous),
+        None => Ok(()),
+    }
+}
+
+fn after_id(
+    hooks: &Hooks,
+    apptbook: &Book,
+    action: Action,
+    id: u64,
+    previous: Option<&Appointment>,
+) {
+    let hit = apptbook.find_id(id).unwrap();
+    after(hooks, action, hit.owner, hit.appt, previous);
+}
+
+// The change already happened, so a failing post-hook is only reported
+fn after(
+    hooks: &Hooks,
+    action: Action,
+    owner: &Owner,
+    appt: &Appointment,
+    previous: Option<&Appointment>,
+) {
+    let command = match action {
+        Action::Add => &hooks.post_add,
+        Action::Edit => &hooks.post_edit,
+        Action::Delete => &hooks.post_delete,
+        Action::Import => &hooks.post_import,
+    };
+
+    if let Some(command) = command {
+        if let Err(message) = run(command, "post", action, owner, appt, previous) {
+            eprintln!("{}", message);
+        }
+    }
+}
+
+// Run a hook with sh, writing the change as JSON to its standard input. A hook fails by exiting
+// with a non-zero status, and what it printed explains why
+fn run(
+    command: &str,
+    stage: &str,
+    action: Action,
+    owner: &Owner,
+    appt: &Appointment,
+    previous: Option<&Appointment>,
+) -> Result<(), String> {
+    let hook = format!("{}-{}", stage, action.name());
+    let payload = serde_json::to_string(&Payload {
+        action: action.name(),
+        owner: OwnerRecord {
+            name: &owner.name,
+            handle: &owner.handle,
+        },
+        appointment: appt,
+        previous,
+    })
+    .unwrap();
+
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("APPTBOOK_HOOK", &hook)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Failed to run the {} hook: {}", hook, err))?;
+
+    // A hook that exits without reading its input closes the pipe, which isn't an error
+    let _ = child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(format!("{}\n", payload).as_bytes());
+
+    let output = child
+        .wait_with_output()
+        .map_err(|err| format!("Failed to run the {} hook: {}", hook, err))?;
+
+    if output.status.success() {
+        return Ok(());
+    }
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let stdout = String::from_utf8_lossy(&output.stdout);
+
+    let reason = match (stderr.trim(), stdout.trim()) {
+        ("", "") => format!("{}", output.status),
+        ("", stdout) => stdout.to_string(),
+        (stderr, _) => stderr.to_string(),
+    };
+
+    if stage == "pre" {
+        Err(format!("The {} hook refused the change: {}", hook, reason))
+    } else {
+        Err(format!("The {} hook failed: {}", hook, reason))
+    }
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+// The hook tests don't depend on when appointments are
+#[cfg(test)]
+fn appt(description: &str) -> Appointment {
+    Appointment::sample(description, "10/19/2026 09:00", "10/19/2026 10:00")
+}
+
+#[test]
+fn pre_hooks_can_refuse_changes() {
+    let hooks = Hooks {
+        pre_add: Some(
+            "grep -q '\"description\":\"Golf\"' && echo 'No golf' >&2 && exit 1; exit 0"
+                .to_string(),
+        ),
+        pre_delete: Some("echo 'Deleting is off' && exit 3".to_string()),
+        ..Default::default()
+    };
+    let mut apptbook = Book::default();
+
+    let id = add(&hooks, &mut apptbook, Action::Add, "Alice", appt("Review")).unwrap();
+    assert_eq!(id, 1);
+
+    assert_eq!(
+        add(&hooks, &mut apptbook, Action::Add, "Alice", appt("Golf")),
+        Err("The pre-add hook refused the change: No golf".to_string())
+    );
+    assert_eq!(apptbook.owners[0].appointments.len(), 1);
+
+    let (deleted, refusals) = delete_ids(&hooks, &mut apptbook, &[id]);
+    assert_eq!(deleted, 0);
+    assert_eq!(
+        refusals,
+        vec!["The pre-delete hook refused the change: Deleting is off".to_string()]
+    );
+    assert!(apptbook.find_id(id).is_some());
+}
+
+#[test]
+fn hooks_receive_the_change() {
+    let log = std::env::temp_dir().join(format!("apptbook-hooks-{}.log", std::process::id()));
+    let append = format!("(echo \"$APPTBOOK_HOOK\"; cat) >> '{}'", log.display());
+    let hooks = Hooks {
+        pre_edit: Some(append.clone()),
+        post_edit: Some(append),
+        // A failing post-hook doesn't undo the change
+        post_delete: Some("exit 1".to_string()),
+        ..Default::default()
+    };
+    let mut apptbook = Book::default();
+    let id = apptbook.add("Alice", appt("Review")).unwrap();
+
+    replace(
+        &hooks,
+        &mut apptbook,
+        Action::Edit,
+        id,
+        "Bob",
+        appt("Review v2"),
+    )
+    .unwrap();
+    assert_eq!(apptbook.find_id(id).unwrap().owner.name, "Bob");
+
+    let lines: Vec<String> = std::fs::read_to_string(&log)
+        .unwrap()
+        .lines()
+        .map(str::to_string)
+        .collect();
+    std::fs::remove_file(&log).unwrap();
+
+    assert_eq!(lines[0], "pre-edit");
+    assert_eq!(lines[2], "post-edit");
+
+    let payload: serde_json::Value = serde_json::from_str(&lines[3]).unwrap();
+    assert_eq!(payload["action"], "edit");
+    assert_eq!(payload["owner"]["handle"], "bob");
+    assert_eq!(payload["appointment"]["id"], id);
+    assert_eq!(payload["appointment"]["description"], "Review v2");
+    assert_eq!(payload["previous"]["description"], "Review");
+
+    assert!(delete(&hooks, &mut apptbook, id).is_ok());
+    assert!(apptbook.find_id(id).is_none());
+    assert!(replace(&hooks, &mut apptbook, Action::Edit, id, "Bob", appt("Gone")).is_err());
+}
+
+#[test]
+fn deleting_for_an_attendee_declines() {
+    use crate::attendees::Attendee;
+    use crate::filter::Filter;
+
+    let mut apptbook = Book::default();
+    let mut planning = appt("Planning");
+    planning.attendees = vec![Attendee::new("Bob")];
+    let planning = apptbook.add("Alice", planning).unwrap();
+    let lunch = apptbook.add("Bob", appt("Lunch")).unwrap();
+
+    // Bob's own appointment is deleted, and the one he's attending stays for Alice
+    let targets: Vec<(u64, String)> = Filter::parse("owner:bob")
+        .unwrap()
+        .select(&apptbook)
+        .iter()
+        .map(|hit| (hit.appt.id, hit.owner.handle.clone()))
+        .collect();
+    assert_eq!(targets.len(), 2);
+
+    assert_eq!(
+        delete_for_owners(&Hooks::default(), &mut apptbook, &targets),
+        (1, 1, Vec::new())
+    );
+    assert!(apptbook.find_id(lunch).is_none());
+    let hit = apptbook.find_id(planning).unwrap();
+    assert_eq!(hit.owner.handle, "alice");
+    assert_eq!(hit.appt.attendees[0].response, Response::Declined);
+    assert!(apptbook.attending("bob").is_empty());
+}
@@ -0,0 +1,479 @@
+use crate::appointment::Status;
+use crate::book::Hit;
+use chrono::prelude::*;
+use chrono::Duration;
+use std::collections::BTreeSet;
+use std::fmt::Write;
+
+// Colors given to owners who haven't chosen one
+const PALETTE: [&str; 8] = [
+    "#4e79a7", "#f28e2b", "#59a14f", "#e15759", "#76b7b2", "#b07aa1", "#edc948", "#9c755f",
+];
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+h2 { margin-top: 2em; }
+table { border-collapse: collapse; width: 100%; table-layout: fixed; }
+th, td { border: 1px solid #ccc; vertical-align: top; padding: 2px 4px; }
+th { background: #f4f4f4; }
+tr.days td { border-bottom: none; font-weight: bold; font-size: 0.9em; }
+tr.lane td, tr.items td { border-top: none; border-bottom: none; }
+tr.items td { height: 4em; }
+td.other { color: #aaa; background: #fafafa; }
+td.hour { width: 4em; color: #666; font-size: 0.9em; }
+.event { font-size: 0.85em; margin: 1px 0; padding: 1px 4px; border-left: 4px solid; }
+.span { color: #fff; border-left: none; border-radius: 3px; }
+.tentative { opacity: 0.6; font-style: italic; }
+.cancelled { opacity: 0.5; text-decoration: line-through; }
+.legend span { display: inline-block; margin-right: 1em; padding: 2px 6px; color: #fff; }
+";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum View {
+    Month,
+    Week,
+    Both,
+}
+
+impl View {
+    pub fn parse(input: &str) -> Result<View, String> {
+        match input {
+            "month" => Ok(View::Month),
+            "week" => Ok(View::Week),
+            "both" => Ok(View::Both),
+            _ => Err("Invalid view. Must be month, week or both".to_string()),
+        }
+    }
+}
+
+// Render the appointments as a standalone HTML page of month and/or week grids
+pub fn render(hits: &[Hit], title: &str, view: View) -> String {
+    let colors = owner_colors(hits);
+    let mut out = String::new();
+
+    writeln!(out, "<!DOCTYPE html>").unwrap();
+    writeln!(out, "<html>\n<head>\n<meta charset=\"utf-8\">").unwrap();
+    writeln!(out, "<title>{}</title>", escape(title)).unwrap();
+    writeln!(out, "<style>{}</style>\n</head>\n<body>", STYLE).unwrap();
+    writeln!(out, "<h1>{}</h1>", escape(title)).unwrap();
+
+    // Legend of owner colors
+    write!(out, "<p class=\"legend\">").unwrap();
+    for (name, color) in &colors {
+        write!(
+            out,
+            "<span style=\"background: {}\">{}</span>",
+            color,
+            escape(name)
+        )
+        .unwrap();
+    }
+    writeln!(out, "</p>").unwrap();
+
+    if hits.is_empty() {
+        writeln!(out, "<p>No appointments</p>").unwrap();
+    }
+
+    let days = days_covered(hits);
+
+    if view != View::Week {
+        let months: BTreeSet<(i32, u32)> =
+            days.iter().map(|day| (day.year(), day.month())).collect();
+
+        for (year, month) in months {
+            render_month(&mut out, hits, &colors, year, month);
+        }
+    }
+
+    if view != View::Month {
+        let mondays: BTreeSet<NaiveDate> = days.iter().map(|day| monday_of(*day)).collect();
+
+        for monday in mondays {
+            render_week(&mut out, hits, &colors, monday);
+        }
+    }
+
+    writeln!(out, "</body>\n</html>").unwrap();
+
+    out
+}
+
+fn render_month(
+    out: &mut String,
+    hits: &[Hit],
+    colors: &[(String, String)],
+    year: i32,
+    month: u32,
+) {
+    let first = NaiveDate::from_ymd(year, month, 1);
+
+    writeln!(
+        out,
+        "<h2>{}</h2>\n<table class=\"month\">",
+        first.format("%B %Y")
+    )
+    .unwrap();
+    write_weekday_header(out, None);
+
+    let mut monday = monday_of(first);
+
+    while monday.year() < year || (monday.year() == year && monday.month() <= month) {
+        write_spanning_rows(out, hits, colors, monday, Some(month));
+
+        // Single-day appointments listed under each day
+        write!(out, "<tr class=\"items\">").unwrap();
+        for day in week(monday) {
+            write_day_cell(out, hits, colors, day, Some(month), |_| true);
+        }
+        writeln!(out, "</tr>").unwrap();
+
+        monday += Duration::weeks(1);
+    }
+
+    writeln!(out, "</table>").unwrap();
+}
+
+fn render_week(out: &mut String, hits: &[Hit], colors: &[(String, String)], monday: NaiveDate) {
+    writeln!(
+        out,
+        "<h2>Week of {}</h2>\n<table class=\"week\">",
+        monday.format("%m/%d/%Y")
+    )
+    .unwrap();
+    write_weekday_header(out, Some(monday));
+    write_spanning_rows(out, hits, colors, monday, None);
+
+    // One row per hour of the day that single-day appointments start in this week
+    let hours: BTreeSet<u32> = hits
+        .iter()
+        .filter(|hit| is_single_day(hit) && week(monday).any(|day| hit.appt.occurs_on(day)))
+        .map(|hit| hit.appt.start_date_time.hour())
+        .collect();
+
+    for hour in hours {
+        write!(
+            out,
+            "<tr class=\"items\"><td class=\"hour\">{:02}:00</td>",
+            hour
+        )
+        .unwrap();
+        for day in week(monday) {
+            write_day_cell(out, hits, colors, day, None, |hit| {
+                hit.appt.start_date_time.hour() == hour
+            });
+        }
+        writeln!(out, "</tr>").unwrap();
+    }
+
+    writeln!(out, "</table>").unwrap();
+}
+
+// Header row of weekday names, with dates and a leading hour column for week views
+fn write_weekday_header(out: &mut String, monday: Option<NaiveDate>) {
+    write!(out, "<tr>").unwrap();
+
+    match monday {
+        Some(monday) => {
+            write!(out, "<th class=\"hour\"></th>").unwrap();
+            for day in week(monday) {
+                write!(out, "<th>{}</th>", day.format("%a %m/%d")).unwrap();
+            }
+        }
+        None => {
+            for day in week(NaiveDate::from_ymd(2001, 1, 1)) {
+                write!(out, "<th>{}</th>", day.format("%a")).unwrap();
+            }
+        }
+    }
+
+    writeln!(out, "</tr>").unwrap();
+}
+
+// Rows at the top of a week: day numbers for month views, then all-day and multi-day appointments
+// as bars spanning the cells of the days they cover. Overlapping bars are stacked in separate
+// lanes
+fn write_spanning_rows(
+    out: &mut String,
+    hits: &[Hit],
+    colors: &[(String, String)],
+    monday: NaiveDate,
+    month: Option<u32>,
+) {
+    let sunday = monday + Duration::days(6);
+
+    if let Some(month) = month {
+        write!(out, "<tr class=\"days\">").unwrap();
+        for day in week(monday) {
+            write!(
+                out,
+                "<td{}>{}</td>",
+                other_class(day, Some(month)),
+                day.day()
+            )
+            .unwrap();
+        }
+        writeln!(out, "</tr>").unwrap();
+    }
+
+    // Each lane holds non-overlapping (first column, last column, hit) bars
+    let mut lanes: Vec<Vec<(i64, i64, &Hit)>> = Vec::new();
+
+    for hit in hits {
+        if is_single_day(hit)
+            || hit.appt.start_date_time.date() > sunday
+            || hit.appt.last_day() < monday
+        {
+            continue;
+        }
+
+        let first = (hit.appt.start_date_time.date() - monday).num_days().max(0);
+        let last = (hit.appt.last_day() - monday).num_days().min(6);
+
+        match lanes
+            .iter_mut()
+            .find(|lane| lane.iter().all(|bar| bar.1 < first || bar.0 > last))
+        {
+            Some(lane) => lane.push((first, last, hit)),
+            None => lanes.push(vec![(first, last, hit)]),
+        }
+    }
+
+    for mut lane in lanes {
+        lane.sort_by_key(|bar| bar.0);
+
+        write!(out, "<tr class=\"lane\">").unwrap();
+
+        if month.is_none() {
+            write!(out, "<td class=\"hour\"></td>").unwrap();
+        }
+
+        let mut column = 0;
+        for (first, last, hit) in lane {
+            for _ in column..first {
+                write!(out, "<td></td>").unwrap();
+            }
+
+            write!(
+                out,
+                "<td colspan=\"{}\"><div class=\"event span{}\" style=\"background: {}\" title=\"{}\">{} ({})</div></td>",
+                last - first + 1,
+                status_class(hit),
+                color_of(colors, &hit.owner.name),
+                hit.appt.formatted_span(),
+                escape(&hit.appt.description),
+                escape(&hit.owner.name)
+            )
+            .unwrap();
+
+            column = last + 1;
+        }
+
+        for _ in column..7 {
+            write!(out, "<td></td>").unwrap();
+        }
+
+        writeln!(out, "</tr>").unwrap();
+    }
+}
+
+// A cell listing the single-day appointments on the day that pass the check
+fn write_day_cell<F>(
+    out: &mut String,
+    hits: &[Hit],
+    colors: &[(String, String)],
+    day: NaiveDate,
+    month: Option<u32>,
+    check: F,
+) where
+    F: Fn(&Hit) -> bool,
+{
+    write!(out, "<td{}>", other_class(day, month)).unwrap();
+
+    for hit in hits {
+        if !is_single_day(hit) || !hit.appt.occurs_on(day) || !check(hit) {
+            continue;
+        }
+
+        write!(
+            out,
+            "<div class=\"event{}\" style=\"border-color: {}\">{}&ndash;{} {} ({})</div>",
+            status_class(hit),
+            color_of(colors, &hit.owner.name),
+            hit.appt.start_date_time.format("%H:%M"),
+            hit.appt.end_date_time.format("%H:%M"),
+            escape(&hit.appt.description),
+            escape(&hit.owner.name)
+        )
+        .unwrap();
+    }
+
+    write!(out, "</td>").unwrap();
+}
+
+// Each owner's chosen color if it's safe to put in a style attribute, otherwise one from the
+// palette. Owners are listed in the order they first appear
+fn owner_colors(hits: &[Hit]) -> Vec<(String, String)> {
+    let mut colors: Vec<(String, String)> = Vec::new();
+
+    for hit in hits {
+        if colors.iter().any(|(name, _)| *name == hit.owner.name) {
+            continue;
+        }
+
+        let color = match &hit.owner.preferences.color {
+            Some(color) if is_safe_color(color) => color.clone(),
+            _ => PALETTE[colors.len() % PALETTE.len()].to_string(),
+        };
+
+        colors.push((hit.owner.name.clone(), color));
+    }
+
+    colors
+}
+
+fn color_of<'a>(colors: &'a [(String, String)], name: &str) -> &'a str {
+    colors
+        .iter()
+        .find(|(owner, _)| owner == name)
+        .map(|(_, color)| color.as_str())
+        .unwrap_or("#888")
+}
+
+// Hex colors like #3366cc, or CSS color names like teal
+fn is_safe_color(color: &str) -> bool {
+    match color.strip_prefix('#') {
+        Some(hex) => {
+            [3, 4, 6, 8].contains(&hex.len()) && hex.chars().all(|c| c.is_ascii_hexdigit())
+        }
+        None => !color.is_empty() && color.chars().all(|c| c.is_ascii_alphabetic()),
+    }
+}
+
+// Tentative and cancelled appointments are styled differently
+fn status_class(hit: &Hit) -> &'static str {
+    match hit.appt.status {
+        Status::Tentative => " tentative",
+        Status::Cancelled => " cancelled",
+        _ => "",
+    }
+}
+
+// Timed appointments within one day are listed in the day's cell. All-day ones are drawn as bars
+// like those spanning several days
+fn is_single_day(hit: &Hit) -> bool {
+    !hit.appt.all_day && hit.appt.start_date_time.date() == hit.appt.last_day()
+}
+
+// Every day any of the appointments occupy
+fn days_covered(hits: &[Hit]) -> BTreeSet<NaiveDate> {
+    let mut days = BTreeSet::new();
+
+    for hit in hits {
+        let mut day = hit.appt.start_date_time.date();
+        while day <= hit.appt.last_day() {
+            days.insert(day);
+            day = day.succ();
+        }
+    }
+
+    days
+}
+
+fn monday_of(day: NaiveDate) -> NaiveDate {
+    day - Duration::days(day.weekday().num_days_from_monday() as i64)
+}
+
+fn week(monday: NaiveDate) -> impl Iterator<Item = NaiveDate> {
+    (0..7).map(move |offset| monday + Duration::days(offset))
+}
+
+// Dim days outside the month being shown
+fn other_class(day: NaiveDate, month: Option<u32>) -> &'static str {
+    match month {
+        Some(month) if day.month() != month => " class=\"other\"",
+        _ => "",
+    }
+}
+
+pub fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn sample_book() -> crate::book::Book {
+    let mut apptbook = crate::book::Book::sample(&[
+        (
+            "Alice",
+            "Dentist <early>",
+            "11/03/2026 09:00",
+            "11/03/2026 10:00",
+        ),
+        ("Bob", "Conference", "11/04/2026 09:00", "11/06/2026 17:00"),
+    ]);
+    apptbook.find_mut("alice").unwrap().preferences.color = Some("#3366cc".to_string());
+    apptbook.find_mut("bob").unwrap().preferences.color = Some("red;}".to_string());
+
+    apptbook
+}
+
+#[test]
+fn render_month_and_week() {
+    let apptbook = sample_book();
+    let page = render(&apptbook.select(|_, _| true), "Office & Co", View::Both);
+
+    assert!(page.starts_with("<!DOCTYPE html>"));
+    assert!(page.contains("<title>Office &amp; Co</title>"));
+    assert!(page.contains("<h2>November 2026</h2>"));
+    assert!(page.contains("<h2>Week of 11/02/2026</h2>"));
+    assert!(page.contains("09:00&ndash;10:00 Dentist &lt;early&gt; (Alice)"));
+
+    // The conference spans Wednesday to Friday in both the month and week grid
+    assert_eq!(page.matches("<td colspan=\"3\">").count(), 2);
+
+    // Alice's chosen color is used, and Bob's unsafe one is replaced from the palette
+    assert!(page.contains("border-color: #3366cc"));
+    assert!(!page.contains("red;}"));
+    assert!(page.contains(&format!("background: {}", PALETTE[1])));
+}
+
+#[test]
+fn render_single_view() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+
+    assert!(!render(&hits, "Book", View::Month).contains("Week of"));
+    assert!(!render(&hits, "Book", View::Week).contains("November 2026"));
+    assert!(View::parse("year").is_err());
+
+    // All-day appointments are bars rather than listed with times
+    let mut apptbook = sample_book();
+    apptbook
+        .add(
+            "Alice",
+            crate::appointment::Appointment::new_all_day(
+                "Holiday",
+                NaiveDate::from_ymd(2026, 11, 2),
+                NaiveDate::from_ymd(2026, 11, 2),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+    let page = render(&apptbook.select(|_, _| true), "Book", View::Week);
+    assert!(page.contains("title=\"All day 11/02/2026\">Holiday (Alice)"));
+    assert!(!page.contains("00:00&ndash;"));
+}
+
+#[test]
+fn safe_colors() {
+    assert!(is_safe_color("#abc"));
+    assert!(is_safe_color("teal"));
+    assert!(!is_safe_color("#abcde"));
+    assert!(!is_safe_color("red; background: url(x)"));
+}
@@ -0,0 +1,550 @@
+use crate::appointment::{Appointment, Status};
+use crate::book::Hit;
+use crate::remind;
+use crate::tags;
+use chrono::prelude::*;
+use chrono::Duration;
+
+// Appointments have no time zone, so they're written as floating times that calendar clients
+// show at the same wall clock time wherever they are
+const DATE_TIME_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+// An event read from an iCalendar object
+pub struct Event {
+    pub uid: Option<String>,
+    pub summary: String,
+    pub start: NaiveDateTime,
+    pub end: NaiveDateTime,
+    // Minutes before the start, from alarms triggered relative to it
+    pub reminders: Vec<i64>,
+    // From CATEGORIES
+    pub tags: Vec<String>,
+    pub location: Option<String>,
+    // From DESCRIPTION
+    pub notes: Option<String>,
+    // Tentative, confirmed or cancelled, if the event has a STATUS
+    pub status: Option<Status>,
+    // Whether DTSTART is a DATE rather than a DATE-TIME
+    pub all_day: bool,
+}
+
+// The UID an appointment is published under. Appointments created by calendar clients keep the
+// client's UID
+pub fn uid(appt: &Appointment) -> String {
+    match &appt.uid {
+        Some(uid) => uid.clone(),
+        None => format!("apptbook-{}", appt.id),
+    }
+}
+
+// A VCALENDAR object holding an event for each appointment
+pub fn calendar(hits: &[Hit]) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        "PRODID:-//apptbook-rust//apptbook//EN".to_string(),
+    ];
+    let stamp = Utc::now().format("%Y%m%dT%H%M%SZ").to_string();
+
+    for hit in hits {
+        lines.push("BEGIN:VEVENT".to_string());
+        lines.push(format!("UID:{}", escape(&uid(hit.appt))));
+        lines.push(format!("DTSTAMP:{}", stamp));
+
+        // All-day appointments are DATE values, with the day after the last as the end
+        if hit.appt.all_day {
+            lines.push(format!(
+                "DTSTART;VALUE=DATE:{}",
+                hit.appt.start_date_time.format("%Y%m%d")
+            ));
+            lines.push(format!(
+                "DTEND;VALUE=DATE:{}",
+                hit.appt.end_date_time.format("%Y%m%d")
+            ));
+        } else {
+            lines.push(format!(
+                "DTSTART:{}",
+                hit.appt.start_date_time.format(DATE_TIME_FORMAT)
+            ));
+            lines.push(format!(
+                "DTEND:{}",
+                hit.appt.end_date_time.format(DATE_TIME_FORMAT)
+            ));
+        }
+
+        lines.push(format!("SUMMARY:{}", escape(&hit.appt.description)));
+
+        lines.push(format!("STATUS:{}", status_name(hit.appt.status)));
+
+        if let Some(location) = &hit.appt.location {
+            lines.push(format!("LOCATION:{}", escape(location)));
+        }
+
+        if let Some(notes) = &hit.appt.notes {
+            lines.push(format!("DESCRIPTION:{}", escape(notes)));
+        }
+
+        if !hit.appt.tags.is_empty() {
+            let categories: Vec<String> = hit.appt.tags.iter().map(|tag| escape(tag)).collect();
+            lines.push(format!("CATEGORIES:{}", categories.join(",")));
+        }
+
+        for &offset in &hit.appt.reminders {
+            lines.push("BEGIN:VALARM".to_string());
+            lines.push("ACTION:DISPLAY".to_string());
+            lines.push(format!("DESCRIPTION:{}", escape(&hit.appt.description)));
+            lines.push(format!("TRIGGER:-{}", format_duration(offset)));
+            lines.push("END:VALARM".to_string());
+        }
+
+        lines.push("END:VEVENT".to_string());
+    }
+
+    lines.push("END:VCALENDAR".to_string());
+
+    let mut out = String::new();
+
+    for line in lines {
+        out.push_str(&fold(&line));
+        out.push_str("\r\n");
+    }
+
+    out
+}
+
+// The event STATUS for an appointment's status. Events can only be tentative, confirmed or
+// cancelled, so completed appointments and no-shows are confirmed ones
+pub fn status_name(status: Status) -> &'static str {
+    match status {
+        Status::Tentative => "TENTATIVE",
+        Status::Cancelled => "CANCELLED",
+        Status::Confirmed | Status::Completed | Status::NoShow => "CONFIRMED",
+    }
+}
+
+// Read the first event from an iCalendar object. Recurring events can't be stored as a single
+// appointment, so they're refused
+pub fn parse_event(text: &str) -> Result<Event, String> {
+    // Long lines are folded by breaking them and starting the continuation with a space or tab
+    let unfolded = text
+        .replace("\r\n", "\n")
+        .replace("\n ", "")
+        .replace("\n\t", "");
+
+    let mut in_event = false;
+    // Depth of components nested in the event, such as alarms
+    let mut nested = 0;
+    let mut in_alarm = false;
+    let mut reminders = Vec::new();
+    let mut categories = Vec::new();
+    let mut location = None;
+    let mut status = None;
+    let mut notes = None;
+    let mut uid = None;
+    let mut summary = String::new();
+    let mut start = None;
+    let mut end = None;
+    let mut duration = None;
+
+    for line in unfolded.lines() {
+        let (name, params, value) = match split_property(line) {
+            Some(property) => property,
+            None => continue,
+        };
+
+        match (name.as_str(), value) {
+            ("BEGIN", "VEVENT") if !in_event => in_event = true,
+            ("BEGIN", _) if in_event => {
+                in_alarm = nested == 0 && value == "VALARM";
+                nested += 1;
+            }
+            ("END", "VEVENT") if in_event && nested == 0 => break,
+            ("END", _) if in_event => {
+                in_alarm = false;
+                nested -= 1;
+            }
+            // Only alarms before the start become reminders
+            ("TRIGGER", _) if in_alarm && nested == 1 => {
+                let params = params.to_uppercase();
+
+                if !params.contains("RELATED=END") && !params.contains("VALUE=DATE-TIME") {
+                    let offset = -parse_duration(value)?.num_minutes();
+
+                    if offset > remind::MAX_OFFSET_MINUTES {
+                        return Err(format!("Invalid duration: {}", value.trim()));
+                    }
+
+                    if offset >= 0 && !reminders.contains(&offset) {
+                        reminders.push(offset);
+                    }
+                }
+            }
+            _ if !in_event || nested > 0 => (),
+            ("UID", _) => uid = Some(unescape(value)),
+            ("SUMMARY", _) => summary = unescape(value),
+            // Tags can't contain commas, so escaped ones separate categories too. They may be
+            // split over several lines
+            ("CATEGORIES", _) => categories.push(unescape(value)),
+            ("LOCATION", _) => location = Some(unescape(value)),
+            ("STATUS", _) => {
+                status = match value.to_uppercase().as_str() {
+                    "TENTATIVE" => Some(Status::Tentative),
+                    "CONFIRMED" => Some(Status::Confirmed),
+                    "CANCELLED" => Some(Status::Cancelled),
+                    _ => None,
+                }
+            }
+            ("DESCRIPTION", _) => notes = Some(unescape(value)),
+            ("DTSTART", _) => start = Some(parse_date_time(params, value)?),
+            ("DTEND", _) => end = Some(parse_date_time(params, value)?.0),
+            ("DURATION", _) => duration = Some(parse_duration(value)?),
+            ("RRULE", _) | ("RDATE", _) => {
+                return Err("Recurring events are not supported".to_string())
+            }
+            _ => (),
+        }
+    }
+
+    if !in_event {
+        return Err("Missing VEVENT".to_string());
+    }
+
+    let (start, all_day) = start.ok_or_else(|| "Missing DTSTART".to_string())?;
+
+    // Without an end, an all-day event lasts the day and any other event is instantaneous
+    let end = match (end, duration) {
+        (Some(end), _) => end,
+        (None, Some(duration)) => start
+            .checked_add_signed(duration)
+            .ok_or_else(|| "Invalid duration. The event would end too late".to_string())?,
+        (None, None) if all_day => start + Duration::days(1),
+        (None, None) => start,
+    };
+
+    Appointment::new(&summary, start, end)?;
+    reminders.sort_unstable();
+
+    Ok(Event {
+        uid,
+        summary,
+        start,
+        end,
+        reminders,
+        tags: tags::parse(&categories.join(",")),
+        // Clients send empty values for fields that were cleared
+        location: location.filter(|location| !location.trim().is_empty()),
+        notes: notes.filter(|notes| !notes.trim().is_empty()),
+        status,
+        all_day,
+    })
+}
+
+// Split a content line like "DTSTART;TZID=Europe/Paris:20261018T100000" into its uppercased name,
+// parameters and value. Parameter values may be quoted and contain colons
+fn split_property(line: &str) -> Option<(String, &str, &str)> {
+    let mut in_quotes = false;
+
+    for (index, c) in line.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ':' if !in_quotes => {
+                let head = &line[..index];
+                let (name, params) = match head.find(';') {
+                    Some(semicolon) => (&head[..semicolon], &head[semicolon + 1..]),
+                    None => (head, ""),
+                };
+
+                return Some((name.trim().to_uppercase(), params, &line[index + 1..]));
+            }
+            _ => (),
+        }
+    }
+
+    None
+}
+
+// Parse a DATE or DATE-TIME value, returning whether it was a date. UTC times are converted to
+// local time. Times with a TZID are taken as wall clock times, since no time zone database is
+// available to convert them
+fn parse_date_time(params: &str, value: &str) -> Result<(NaiveDateTime, bool), String> {
+    let value = value.trim();
+    let invalid = || format!("Invalid date/time: {}", value);
+
+    if (params.to_uppercase().contains("VALUE=DATE") && !value.contains('T')) || value.len() == 8 {
+        return NaiveDate::parse_from_str(value, "%Y%m%d")
+            .map(|date| (date.and_hms(0, 0, 0), true))
+            .map_err(|_| invalid());
+    }
+
+    match value.strip_suffix('Z') {
+        Some(utc) => NaiveDateTime::parse_from_str(utc, DATE_TIME_FORMAT)
+            .map(|utc| (Local.from_utc_datetime(&utc).naive_local(), false))
+            .map_err(|_| invalid()),
+        None => NaiveDateTime::parse_from_str(value, DATE_TIME_FORMAT)
+            .map(|date_time| (date_time, false))
+            .map_err(|_| invalid()),
+    }
+}
+
+// Parse a UTC time as used in CalDAV time ranges, e.g. "20261018T000000Z", into local time
+pub fn parse_utc(value: &str) -> Result<NaiveDateTime, String> {
+    match value.strip_suffix('Z') {
+        Some(_) => parse_date_time("", value).map(|(date_time, _)| date_time),
+        None => Err(format!("Invalid UTC time: {}", value)),
+    }
+}
+
+// Parse a duration like "PT1H30M", "P1D" or "P2W"
+fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration: {}", value);
+    let value = value.trim();
+
+    let (negative, rest) = match value.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, value.strip_prefix('+').unwrap_or(value)),
+    };
+
+    let rest = rest.strip_prefix('P').ok_or_else(invalid)?;
+    let mut seconds: i64 = 0;
+    let mut number = String::new();
+    let mut in_time = false;
+
+    for c in rest.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+
+        if c == 'T' {
+            in_time = true;
+            continue;
+        }
+
+        let n: i64 = number.parse().map_err(|_| invalid())?;
+        number.clear();
+
+        let part = match (c, in_time) {
+            ('W', false) => n.checked_mul(7 * 24 * 60 * 60),
+            ('D', false) => n.checked_mul(24 * 60 * 60),
+            ('H', true) => n.checked_mul(60 * 60),
+            ('M', true) => n.checked_mul(60),
+            ('S', true) => Some(n),
+            _ => return Err(invalid()),
+        };
+
+        // Clients can send anything, so sums too large for a Duration are invalid, not a panic
+        seconds = part
+            .and_then(|part| seconds.checked_add(part))
+            .ok_or_else(invalid)?;
+    }
+
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+
+    let total = seconds
+        .checked_mul(1000)
+        .map(Duration::milliseconds)
+        .ok_or_else(invalid)?;
+
+    Ok(if negative { -total } else { total })
+}
+
+// Format minutes as a duration like "PT15M", "PT2H" or "P1D"
+fn format_duration(minutes: i64) -> String {
+    if minutes > 0 && minutes % (24 * 60) == 0 {
+        format!("P{}D", minutes / (24 * 60))
+    } else if minutes > 0 && minutes % 60 == 0 {
+        format!("PT{}H", minutes / 60)
+    } else {
+        format!("PT{}M", minutes)
+    }
+}
+
+// Escape text values, in which backslashes, semicolons, commas and line breaks are special
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n') | Some('N') => unescaped.push('\n'),
+                Some(other) => unescaped.push(other),
+                None => unescaped.push('\\'),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+
+    unescaped
+}
+
+// Break lines longer than 75 bytes, continuing them on lines starting with a space
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut length = 0;
+
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+
+        folded.push(c);
+        length += c.len_utf8();
+    }
+
+    folded
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn calendar_escapes_and_folds() {
+    let mut apptbook = crate::book::Book::default();
+    apptbook
+        .add(
+            "Alice",
+            Appointment {
+                description: "Review; draft, v2".to_string() + &" and more".repeat(8),
+                start_date_time: NaiveDate::from_ymd(2026, 10, 18).and_hms(10, 0, 0),
+                end_date_time: NaiveDate::from_ymd(2026, 10, 18).and_hms(11, 0, 0),
+                reminders: vec![15, 24 * 60],
+                tags: vec!["client".to_string(), "q4".to_string()],
+                location: Some("Room 4, 2nd floor".to_string()),
+                notes: Some("Agenda:\n- Budget; Q4".to_string()),
+                status: Status::Tentative,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let calendar = calendar(&apptbook.select(|_, _| true));
+
+    assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+    assert!(calendar.contains("\r\nUID:apptbook-1\r\n"));
+    assert!(calendar.contains("\r\nDTSTART:20261018T100000\r\nDTEND:20261018T110000\r\n"));
+    assert!(calendar.contains("SUMMARY:Review\\; draft\\, v2 and more"));
+    assert!(calendar.contains("\r\nTRIGGER:-PT15M\r\n"));
+    assert!(calendar.contains("\r\nTRIGGER:-P1D\r\n"));
+    assert!(calendar.lines().all(|line| line.len() <= 76));
+
+    let event = parse_event(&calendar).unwrap();
+    assert_eq!(event.uid.as_deref(), Some("apptbook-1"));
+    assert_eq!(
+        event.summary,
+        apptbook.owners[0].appointments[0].description
+    );
+    assert_eq!(event.reminders, vec![15, 24 * 60]);
+    assert!(calendar.contains("\r\nCATEGORIES:client,q4\r\n"));
+    assert_eq!(event.tags, vec!["client", "q4"]);
+    assert!(calendar.contains("\r\nLOCATION:Room 4\\, 2nd floor\r\n"));
+    assert!(calendar.contains("\r\nDESCRIPTION:Agenda:\\n- Budget\\; Q4\r\n"));
+    assert_eq!(event.location.as_deref(), Some("Room 4, 2nd floor"));
+    assert_eq!(event.notes.as_deref(), Some("Agenda:\n- Budget; Q4"));
+    assert!(calendar.contains("\r\nSTATUS:TENTATIVE\r\n"));
+    assert_eq!(event.status, Some(Status::Tentative));
+    assert!(!event.all_day);
+
+    // All-day appointments are exported as dates, ending the day after the last
+    let vacation = Appointment::new_all_day(
+        "Vacation",
+        NaiveDate::from_ymd(2026, 10, 19),
+        NaiveDate::from_ymd(2026, 10, 21),
+    )
+    .unwrap();
+    let owner = crate::owner::Owner::new("Alice");
+    let calendar = self::calendar(&[Hit {
+        owner: &owner,
+        appt: &vacation,
+        organizer: &owner,
+    }]);
+    assert!(calendar.contains("\r\nDTSTART;VALUE=DATE:20261019\r\nDTEND;VALUE=DATE:20261022\r\n"));
+    let event = parse_event(&calendar).unwrap();
+    assert!(event.all_day);
+    assert_eq!(event.end, vacation.end_date_time);
+}
+
+#[test]
+fn parse_event_times() {
+    let event = parse_event(
+        "BEGIN:VCALENDAR\nBEGIN:VEVENT\nUID:abc@example.com\n\
+         DTSTART;TZID=\"America/Los_Angeles\":20261018T100000\nDURATION:PT1H30M\n\
+         SUMMARY:Lunch\\nwith Lisa\nBEGIN:VALARM\nDESCRIPTION:Ignored\nEND:VALARM\n\
+         END:VEVENT\nEND:VCALENDAR\n",
+    )
+    .unwrap();
+
+    assert_eq!(event.summary, "Lunch\nwith Lisa");
+    assert!(event.reminders.is_empty());
+
+    let categories = parse_event(
+        "BEGIN:VEVENT\nDTSTART:20261018T100000\nCATEGORIES:Work,Client Meeting\n\
+         CATEGORIES:a\\,b\nEND:VEVENT",
+    )
+    .unwrap();
+    assert_eq!(categories.tags, vec!["a", "b", "client-meeting", "work"]);
+
+    let alarms = parse_event(
+        "BEGIN:VEVENT\nDTSTART:20261018T100000\n\
+         BEGIN:VALARM\nTRIGGER;RELATED=START:-PT30M\nEND:VALARM\n\
+         BEGIN:VALARM\nTRIGGER;RELATED=END:-PT5M\nEND:VALARM\n\
+         BEGIN:VALARM\nTRIGGER:-P1D\nEND:VALARM\nEND:VEVENT",
+    )
+    .unwrap();
+    assert_eq!(alarms.reminders, vec![30, 24 * 60]);
+    assert_eq!(
+        event.start,
+        NaiveDate::from_ymd(2026, 10, 18).and_hms(10, 0, 0)
+    );
+    assert_eq!(
+        event.end,
+        NaiveDate::from_ymd(2026, 10, 18).and_hms(11, 30, 0)
+    );
+
+    let all_day = parse_event("BEGIN:VEVENT\nDTSTART;VALUE=DATE:20261018\nEND:VEVENT").unwrap();
+    assert_eq!(
+        all_day.end,
+        NaiveDate::from_ymd(2026, 10, 19).and_hms(0, 0, 0)
+    );
+    assert!(all_day.all_day);
+
+    let utc = parse_event("BEGIN:VEVENT\nDTSTART:20261018T100000Z\nEND:VEVENT").unwrap();
+    let expected = Local.from_utc_datetime(&NaiveDate::from_ymd(2026, 10, 18).and_hms(10, 0, 0));
+    assert_eq!(utc.start, expected.naive_local());
+
+    assert!(
+        parse_event("BEGIN:VEVENT\nDTSTART:20261018T100000\nRRULE:FREQ=DAILY\nEND:VEVENT").is_err()
+    );
+    assert!(parse_event(
+        "BEGIN:VEVENT\nDTSTART:20261018T100000\nDTEND:20261018T090000\nEND:VEVENT"
+    )
+    .is_err());
+    assert!(parse_event("BEGIN:VTODO\nEND:VTODO").is_err());
+    assert_eq!(
+        parse_duration("-P1W2DT3H").unwrap(),
+        -Duration::hours(9 * 24 + 3)
+    );
+    assert!(parse_duration("PT5").is_err());
+    assert!(parse_duration("P9223372036854775807W").is_err());
+    assert!(parse_duration("P99999999999999D").is_err());
+    assert!(
+        parse_event("BEGIN:VEVENT\nDTSTART:20261018T100000\nDURATION:P99999999W\nEND:VEVENT")
+            .is_err()
+    );
+    assert!(parse_event(
+        "BEGIN:VEVENT\nDTSTART:20261018T100000\nBEGIN:VALARM\nTRIGGER:-P9999999W\n\
+         END:VALARM\nEND:VEVENT"
+    )
+    .is_err());
+}
@@ -0,0 +1,278 @@
+use crate::appointment::{Appointment, Status};
+use crate::attendees::Attendee;
+use crate::book::Hit;
+use crate::csv::{Row, RowError};
+use crate::remind;
+use crate::tags;
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+// One appointment per line. This format is documented in the README and scripts rely on it, so
+// fields may be added but never renamed or removed. Times are ISO 8601 without a time zone, e.g.
+// "2026-10-18T10:00:00"
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    // Optional on import. An ID already in the book replaces that appointment
+    #[serde(default)]
+    id: Option<u64>,
+    owner: String,
+    // Optional on import. Takes precedence over owner when matching an existing owner
+    #[serde(default)]
+    owner_handle: Option<String>,
+    description: String,
+    start: NaiveDateTime,
+    // On import, either end or duration_minutes must be given. End wins if both are
+    #[serde(default)]
+    end: Option<NaiveDateTime>,
+    #[serde(default)]
+    duration_minutes: Option<i64>,
+    // Minutes before the start. Left out when there are none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    reminders: Vec<i64>,
+    // Left out when there are none
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    location: Option<String>,
+    // May span several lines
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    notes: Option<String>,
+    // Owners other than the organizer taking part. On import, attendees may be given by name
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    attendees: Vec<Attendee>,
+    // Left out when confirmed
+    #[serde(default, skip_serializing_if = "Status::is_confirmed")]
+    status: Status,
+    // Left out when false. On import, the appointment covers every day from start to end, and
+    // lasts the day if neither end nor duration_minutes is given
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    all_day: bool,
+}
+
+impl Record {
+    pub fn from_hit(hit: &Hit) -> Record {
+        Record {
+            id: Some(hit.appt.id),
+            owner: hit.organizer.name.clone(),
+            owner_handle: Some(hit.organizer.handle.clone()),
+            description: hit.appt.description.clone(),
+            start: hit.appt.start_date_time,
+            end: Some(hit.appt.end_date_time),
+            duration_minutes: Some(hit.appt.duration_minutes()),
+            reminders: hit.appt.reminders.clone(),
+            tags: hit.appt.tags.clone(),
+            location: hit.appt.location.clone(),
+            notes: hit.appt.notes.clone(),
+            attendees: hit.appt.attendees.clone(),
+            status: hit.appt.status,
+            all_day: hit.appt.all_day,
+        }
+    }
+}
+
+pub fn export(hits: &[Hit]) -> String {
+    let mut output = String::new();
+
+    for hit in hits {
+        output.push_str(&serde_json::to_string(&Record::from_hit(hit)).unwrap());
+        output.push('\n');
+    }
+
+    output
+}
+
+// Parse and validate every line, collecting errors rather than stopping at the first bad line.
+// Imported appointments keep the ID from their line, if any, so the caller can replace them
+pub fn import(input: &str) -> (Vec<Row>, Vec<RowError>) {
+    let mut rows = Vec::new();
+    let mut errors = Vec::new();
+
+    for (index, text) in input.lines().enumerate() {
+        // Skip blank lines
+        if text.trim().is_empty() {
+            continue;
+        }
+
+        match parse_record(text) {
+            Ok(row) => rows.push(row),
+            Err(message) => errors.push(RowError {
+                line: index + 1,
+                message,
+            }),
+        }
+    }
+
+    (rows, errors)
+}
+
+// Parse and validate a single record, such as one line of an import. The record's ID is kept, or 0
+// if it has none
+pub fn parse_record(text: &str) -> Result<Row, String> {
+    let record: Record =
+        serde_json::from_str(text).map_err(|err| format!("Invalid JSON: {}", err))?;
+
+    let owner = record.owner_handle.unwrap_or(record.owner);
+
+    if owner.trim().is_empty() {
+        return Err("Owner cannot be empty".to_string());
+    }
+
+    let start = record.start;
+    let end = match (record.end, record.duration_minutes) {
+        (Some(end), _) => end,
+        // Bounded like reminders so the addition can't overflow
+        (None, Some(minutes)) => Some(minutes)
+            .filter(|minutes| (0..=remind::MAX_OFFSET_MINUTES).contains(minutes))
+            .and_then(|minutes| start.checked_add_signed(Duration::minutes(minutes)))
+            .ok_or_else(|| format!("Invalid duration_minutes: {}", minutes))?,
+        (None, None) if record.all_day => record.start,
+        (None, None) => return Err("Missing end or duration_minutes".to_string()),
+    };
+
+    let mut appt = Appointment::new(&record.description, record.start, end)?;
+
+    if record.all_day {
+        appt = Appointment::new_all_day(&record.description, record.start.date(), appt.last_day())?;
+    }
+
+    appt.id = record.id.unwrap_or(0);
+
+    if record.reminders.iter().any(|&offset| offset < 0) {
+        return Err("Reminders cannot be negative".to_string());
+    }
+
+    if let Some(offset) = record
+        .reminders
+        .iter()
+        .find(|&&offset| offset > remind::MAX_OFFSET_MINUTES)
+    {
+        return Err(format!("Invalid reminder: {}", offset));
+    }
+
+    appt.reminders = record.reminders;
+    appt.reminders.sort_unstable();
+    appt.reminders.dedup();
+    appt.tags = tags::parse(&record.tags.join(","));
+    // Empty strings are the same as leaving them out
+    appt.location = record
+        .location
+        .map(|location| location.trim().to_string())
+        .filter(|location| !location.is_empty());
+    appt.notes = record
+        .notes
+        .map(|notes| notes.trim_end().to_string())
+        .filter(|notes| !notes.trim().is_empty());
+    appt.attendees = record.attendees;
+    appt.status = record.status;
+
+    Ok(Row {
+        owner: owner.trim().to_string(),
+        appt,
+    })
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn export_then_import_round_trip() {
+    use crate::book::Book;
+
+    let mut apptbook = Book::default();
+    apptbook
+        .add(
+            "Tom  Smith",
+            Appointment {
+                description: "Lunch with \"Lisa\"".to_string(),
+                start_date_time: NaiveDateTime::parse_from_str(
+                    "02/02/2020 11:30",
+                    "%m/%d/%Y %H:%M",
+                )
+                .unwrap(),
+                end_date_time: NaiveDateTime::parse_from_str("02/02/2020 12:15", "%m/%d/%Y %H:%M")
+                    .unwrap(),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+    let exported = export(&apptbook.select(|_, _| true));
+
+    assert_eq!(
+        exported,
+        "{\"id\":1,\"owner\":\"Tom Smith\",\"owner_handle\":\"tom-smith\",\
+         \"description\":\"Lunch with \\\"Lisa\\\"\",\"start\":\"2020-02-02T11:30:00\",\
+         \"end\":\"2020-02-02T12:15:00\",\"duration_minutes\":45}\n"
+    );
+
+    let (rows, errors) = import(&exported);
+
+    assert!(errors.is_empty());
+    assert_eq!(rows[0].owner, "tom-smith");
+    assert_eq!(rows[0].appt.id, 1);
+    assert_eq!(rows[0].appt.description, "Lunch with \"Lisa\"");
+    assert_eq!(rows[0].appt.duration_minutes(), 45);
+
+    let row = parse_record(
+        "{\"owner\":\"Tom\",\"description\":\"Review\",\"start\":\"2020-02-02T13:00:00\",\
+         \"duration_minutes\":30,\"location\":\" Room 4 \",\"notes\":\"Agenda:\\n- Budget\\n\"}",
+    )
+    .unwrap();
+    assert_eq!(row.appt.location.as_deref(), Some("Room 4"));
+    assert_eq!(row.appt.notes.as_deref(), Some("Agenda:\n- Budget"));
+    assert!(!row.appt.all_day);
+    assert!(export(&[Hit {
+        owner: &crate::owner::Owner::new("Tom"),
+        appt: &row.appt,
+        organizer: &crate::owner::Owner::new("Tom"),
+    }])
+    .contains(",\"location\":\"Room 4\",\"notes\":\"Agenda:\\n- Budget\"}"));
+}
+
+#[test]
+fn import_reports_bad_lines() {
+    let input = "{\"owner\":\"Tom\",\"description\":\"Nap\",\"start\":\"2020-02-02T13:00:00\",\"duration_minutes\":30}\n\
+                 \n\
+                 not json\n\
+                 {\"owner\":\"Tom\",\"description\":\"Open\",\"start\":\"2020-02-02T13:00:00\"}\n\
+                 {\"owner\":\"Tom\",\"description\":\"Backwards\",\"start\":\"2020-02-02T13:00:00\",\"end\":\"2020-02-02T12:00:00\"}\n\
+                 {\"owner\":\" \",\"description\":\"Nobody\",\"start\":\"2020-02-02T13:00:00\",\"duration_minutes\":30}\n";
+
+    let (rows, errors) = import(input);
+
+    assert_eq!(rows.len(), 1);
+    assert_eq!(rows[0].appt.id, 0);
+    assert_eq!(rows[0].appt.duration_minutes(), 30);
+
+    let row = parse_record(
+        "{\"owner\":\"Tom\",\"description\":\"Holiday\",\"start\":\"2020-02-03T09:00:00\",\"all_day\":true}",
+    )
+    .unwrap();
+    assert_eq!(row.appt.formatted_span(), "All day 02/03/2020");
+    assert!(export(&[Hit {
+        owner: &crate::owner::Owner::new("Tom"),
+        appt: &row.appt,
+        organizer: &crate::owner::Owner::new("Tom"),
+    }])
+    .contains("\"start\":\"2020-02-03T00:00:00\",\"end\":\"2020-02-04T00:00:00\",\"duration_minutes\":1440,\"all_day\":true}"));
+
+    let lines: Vec<usize> = errors.iter().map(|error| error.line).collect();
+    assert_eq!(lines, vec![3, 4, 5, 6]);
+    assert!(errors[0].message.starts_with("Invalid JSON"));
+    assert_eq!(errors[1].message, "Missing end or duration_minutes");
+
+    let invalid = parse_record(
+        "{\"owner\":\"Tom\",\"description\":\"Forever\",\"start\":\"2020-02-02T13:00:00\",\
+         \"duration_minutes\":99999999999999}",
+    );
+    assert_eq!(
+        invalid.err().unwrap(),
+        "Invalid duration_minutes: 99999999999999"
+    );
+
+    let invalid = parse_record(
+        "{\"owner\":\"Tom\",\"description\":\"Early\",\"start\":\"2020-02-02T13:00:00\",\
+         \"duration_minutes\":30,\"reminders\":[99999999999999]}",
+    );
+    assert_eq!(invalid.err().unwrap(), "Invalid reminder: 99999999999999");
+}
@@ -5,62 +5,86 @@
 // Course Project apptbook-rust
 //====================================
 
+mod agenda;
+mod appointment;
+mod archive;
+mod attendees;
+mod book;
+mod caldav;
+mod cli;
+mod config;
+mod csv;
+mod filter;
+mod grid;
+mod hooks;
+mod html;
+mod ical;
+mod jsonl;
+mod output;
+mod owner;
+mod prompt;
+mod remind;
+mod report;
+mod search;
+mod server;
+mod tags;
+mod templates;
+mod tui;
+
+use appointment::Appointment;
+use book::Book;
 use chrono::prelude::*;
+use config::Config;
 use regex::Regex;
-use serde::{Deserialize, Serialize};
+#[cfg(test)]
 use std::collections::HashMap;
-use std::fs;
-use std::fs::OpenOptions;
+use std::env;
 use std::io;
-use std::io::Read;
 use std::io::Write;
-
-#[derive(Serialize, Deserialize, Debug)]
-struct Appointment {
-    description: String,
-    start_date_time: NaiveDateTime,
-    end_date_time: NaiveDateTime,
-}
+use std::process;
 
 fn main() {
-    // date regex received from https://stackoverflow.com/questions/15491894/regex-to-validate-date-format-dd-mm-yyyy
-    let date_re =
-        Regex::new(r"^(((0[13-9]|1[012])[/](0[1-9]|[12][0-9]|30)|(0[13578]|1[02])[/]31|02[/](0[1-9]|1[0-9]|2[0-8]))[/][0-9]{4}|02[/]29[/]([0-9]{2}(([2468][048]|[02468][48])|[13579][26])|([13579][26]|[02468][048]|0[0-9]|1[0-6])00))$").unwrap();
+    // If a command was given on the command line, run it instead of the interactive menu
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    // time regex received from https://stackoverflow.com/questions/7536755/regular-expression-for-matching-hhmm-time-format/7536768
-    let time_re = Regex::new(r"^(0[0-9]|1[0-9]|2[0-3]):[0-5][0-9]$").unwrap();
-
-    // String to hold read-in HashMap from storage file
-    let mut appts = String::new();
-
-    // Attempt to read-in storage file. If file doesn't exist, create one
-    match OpenOptions::new()
-        .read(true)
-        .write(true)
-        .create(true)
-        .open("apptbook.txt")
-    {
-        Ok(ref mut file) => {
-            file.read_to_string(&mut appts)
-                .expect("Failed to read file");
-        }
+    let config = match Config::load(config::CONFIG_FILE) {
+        Ok(config) => config,
         Err(err) => {
-            panic!("Failed to open file: {}", err);
+            eprintln!("{}", err);
+            process::exit(1);
         }
-    }
+    };
 
-    // If String empty, allocate with "{}" to represent an empty HashMap
-    if appts.is_empty() {
-        appts.push_str("{}");
+    if !args.is_empty() {
+        let mut apptbook = load_book(&config);
+
+        match cli::run(&args, &mut apptbook, &config) {
+            Ok(true) => apptbook.save(book::STORAGE_FILE),
+            Ok(false) => (),
+            Err(err) => {
+                eprintln!("{}", err);
+                process::exit(1);
+            }
+        }
+
+        return;
     }
 
-    let mut apptbook: HashMap<String, Vec<Appointment>> = serde_json::from_str(&appts).unwrap();
+    let date_re = Regex::new(appointment::DATE_PATTERN).unwrap();
+    let time_re = Regex::new(appointment::TIME_PATTERN).unwrap();
+
+    let mut apptbook = load_book(&config);
 
     loop {
         println!("\n1) Add appointment");
         println!("2) View appointments");
         println!("3) Delete appointments");
-        println!("4) Quit\n");
+        println!("4) Manage owners");
+        println!("5) Search appointments");
+        println!("6) Today's appointments");
+        println!("7) Tomorrow's appointments");
+        println!("8) Next appointment");
+        println!("9) Quit\n");
 
         print!("Enter an option number: ");
         io::stdout().flush().unwrap();
@@ -89,19 +113,39 @@ fn main() {
             let mut end_date = String::new();
             let mut end_time = String::new();
 
-            // Prompt for owner
-            print!("\nEnter the appointment's owner: ");
-            io::stdout().flush().unwrap();
-            io::stdin()
-                .read_line(&mut owner)
-                .expect("Failed to read line");
+            // Prompt for owner. If invalid input, try again
+            loop {
+                print!("\nEnter the appointment's owner: ");
+                io::stdout().flush().unwrap();
+                io::stdin()
+                    .read_line(&mut owner)
+                    .expect("Failed to read line");
+
+                // Check that the owner has a name
+                match owner::check_name(&owner) {
+                    Ok(()) => break,
+                    Err(err) => {
+                        println!("{}", err);
+                        owner = "".to_string();
+                    }
+                }
+            }
 
-            // Prompt for description
-            print!("Enter the description: ");
-            io::stdout().flush().unwrap();
-            io::stdin()
-                .read_line(&mut description)
-                .expect("Failed to read line");
+            // Prompt for a template, if there are any. An appointment from a template only
+            // needs a start
+            let template = read_template(&apptbook);
+
+            // Prompt for description, unless it comes from the template
+            match &template {
+                Some(template) => description = template.description.clone(),
+                None => {
+                    print!("Enter the description: ");
+                    io::stdout().flush().unwrap();
+                    io::stdin()
+                        .read_line(&mut description)
+                        .expect("Failed to read line");
+                }
+            }
 
             // Prompt for start date. If invalid input, try again
             loop {
@@ -112,7 +156,7 @@ fn main() {
                     .expect("Failed to read line");
 
                 // Check that start_date matches the date regex
-                if date_re.is_match(&start_date.trim()) {
+                if date_re.is_match(start_date.trim()) {
                     break;
                 } else {
                     println!("Invalid date. Required format: mm/dd/yyyy\n");
@@ -120,16 +164,23 @@ fn main() {
                 }
             }
 
-            // Prompt for start time. If invalid input, try again
+            // Prompt for start time. If invalid input, try again. No start time makes an all-day
+            // appointment, unless it's from a template
             loop {
-                print!("Enter the start time: ");
+                if template.is_some() {
+                    print!("Enter the start time: ");
+                } else {
+                    print!("Enter the start time (or enter for all day): ");
+                }
                 io::stdout().flush().unwrap();
                 io::stdin()
                     .read_line(&mut start_time)
                     .expect("Failed to read line");
 
                 // Check that start_time matches the time regex
-                if time_re.is_match(&start_time.trim()) {
+                if (start_time.trim().is_empty() && template.is_none())
+                    || time_re.is_match(start_time.trim())
+                {
                     break;
                 } else {
                     println!("Invalid time. Required (24-hour clock) format: hh:mm\n");
@@ -137,6 +188,30 @@ fn main() {
                 }
             }
 
+            // The template gives the rest of the appointment
+            if let Some(template) = &template {
+                let start_date_time = format!("{} {}", start_date.trim(), start_time.trim());
+                let sdt =
+                    NaiveDateTime::parse_from_str(&start_date_time, "%m/%d/%Y %H:%M").unwrap();
+
+                let result = template.appointment(sdt).and_then(|appt| {
+                    hooks::add(
+                        &config.hooks,
+                        &mut apptbook,
+                        hooks::Action::Add,
+                        owner.trim(),
+                        appt,
+                    )
+                });
+
+                match result {
+                    Ok(_) => println!("\nAppointment added successfully"),
+                    Err(err) => println!("\n{}", err),
+                }
+
+                continue;
+            }
+
             // Prompt for end date. If invalid input, try again
             loop {
                 print!("Enter the end date: ");
@@ -146,7 +221,7 @@ fn main() {
                     .expect("Failed to read line");
 
                 // Check that end_date matches the date regex
-                if date_re.is_match(&end_date.trim()) {
+                if date_re.is_match(end_date.trim()) {
                     // setup start_date and end_date for validation
                     let formatted_sd = format!("{} {}", start_date.trim(), "00:00");
                     let formatted_ed = format!("{} {}", end_date.trim(), "00:00");
@@ -169,83 +244,122 @@ fn main() {
                 }
             }
 
-            // Prompt for end time. If invalid input, try again
-            loop {
-                print!("Enter the end time: ");
-                io::stdout().flush().unwrap();
-                io::stdin()
-                    .read_line(&mut end_time)
-                    .expect("Failed to read line");
-
-                // Check that end_time matches the time regex
-                if time_re.is_match(&end_time.trim()) {
-                    // Setup start_time and end_time for validation
-                    let formatted_st = format!("{} {}", start_date.trim(), start_time.trim());
-                    let formatted_et = format!("{} {}", end_date.trim(), end_time.trim());
-                    let st =
-                        NaiveDateTime::parse_from_str(&formatted_st, "%m/%d/%Y %H:%M").unwrap();
-                    let et =
-                        NaiveDateTime::parse_from_str(&formatted_et, "%m/%d/%Y %H:%M").unwrap();
-
-                    // Make sure end_time doesn't occur before start_time
-                    if et < st {
-                        println!("Invalid time. End time cannot occur before start time\n");
-                        end_time = "".to_string();
+            let all_day = start_time.trim().is_empty();
+
+            // Prompt for end time, unless the appointment is all-day. If invalid input, try again
+            if !all_day {
+                loop {
+                    print!("Enter the end time: ");
+                    io::stdout().flush().unwrap();
+                    io::stdin()
+                        .read_line(&mut end_time)
+                        .expect("Failed to read line");
+
+                    // Check that end_time matches the time regex
+                    if time_re.is_match(end_time.trim()) {
+                        // Setup start_time and end_time for validation
+                        let formatted_st = format!("{} {}", start_date.trim(), start_time.trim());
+                        let formatted_et = format!("{} {}", end_date.trim(), end_time.trim());
+                        let st =
+                            NaiveDateTime::parse_from_str(&formatted_st, "%m/%d/%Y %H:%M").unwrap();
+                        let et =
+                            NaiveDateTime::parse_from_str(&formatted_et, "%m/%d/%Y %H:%M").unwrap();
+
+                        // Make sure end_time doesn't occur before start_time
+                        if et < st {
+                            println!("Invalid time. End time cannot occur before start time\n");
+                            end_time = "".to_string();
+                        } else {
+                            // The end_time is approved
+                            break;
+                        }
                     } else {
-                        // The end_time is approved
-                        break;
+                        println!("Invalid time. Required (24-hour clock) format: hh:mm\n");
+                        end_time = "".to_string();
                     }
-                } else {
-                    println!("Invalid time. Required (24-hour clock) format: hh:mm\n");
-                    end_time = "".to_string();
                 }
             }
 
+            // Prompt for reminders. If invalid input, try again
+            let reminders = loop {
+                let input = prompt::read_line(
+                    "Enter reminders before the start (e.g. 15m, 1h, 1d, or enter for none): ",
+                );
+
+                match remind::parse_offsets(&input) {
+                    Ok(reminders) => break reminders,
+                    Err(err) => println!("{}\n", err),
+                }
+            };
+
+            let tags = tags::parse(&prompt::read_line(
+                "Enter tags (comma-separated, or enter for none): ",
+            ));
+
+            let location = Some(prompt::read_line(
+                "Enter the location (or enter for none): ",
+            ))
+            .filter(|location| !location.is_empty());
+            let attendees = attendees::parse(&prompt::read_line(
+                "Enter other owners attending (comma-separated, or enter for none): ",
+            ));
+
+            let notes =
+                prompt::read_lines("Enter notes, ending with an empty line (or enter for none):");
+
             owner = owner.trim().to_string();
             description = description.trim().to_string();
 
+            // All-day appointments run from midnight on the start date to midnight after the end
+            // date
+            if all_day {
+                start_time = "00:00".to_string();
+                end_time = "00:00".to_string();
+            }
+
             // Format start_date/time and end_date/time into one String
             let start_date_time = format!("{} {}", start_date.trim(), start_time.trim());
             let end_date_time = format!("{} {}", end_date.trim(), end_time.trim());
 
             // Parse start_date_time and end_date_time into NaiveDateTimes
             let sdt = NaiveDateTime::parse_from_str(&start_date_time, "%m/%d/%Y %H:%M").unwrap();
-            let edt = NaiveDateTime::parse_from_str(&end_date_time, "%m/%d/%Y %H:%M").unwrap();
+            let mut edt = NaiveDateTime::parse_from_str(&end_date_time, "%m/%d/%Y %H:%M").unwrap();
+
+            if all_day {
+                edt += chrono::Duration::days(1);
+            }
 
             let appt = Appointment {
                 description,
                 start_date_time: sdt,
                 end_date_time: edt,
+                reminders,
+                tags,
+                location,
+                notes,
+                attendees,
+                all_day,
+                ..Default::default()
             };
 
-            // Clone owner to use again after the move
-            let to_sort = owner.clone();
-
-            // Push owner's Appointment to their Vec in the HashMap. If the owner doesn't already
-            // exist within the HashMap, create for them a new Vec, then push the Appointment
-            apptbook.entry(owner).or_insert_with(Vec::new).push(appt);
-
-            let owners_vec = apptbook.get_mut(&to_sort).unwrap();
-
-            // Sort owner's Appointment Vec by start_date_time. If start_date_times are equal, sort
-            // by end_date_time. If end_date_times are equal, sort by description (alphabetically)
-            owners_vec.sort_by(|a, b| {
-                a.start_date_time
-                    .cmp(&b.start_date_time)
-                    .then(a.end_date_time.cmp(&b.end_date_time))
-                    .then(a.description.cmp(&b.description))
-            });
-
-            println!("\nAppointment added successfully");
+            // Push owner's Appointment to their Vec in the book. If the owner doesn't already
+            // exist within the book, create them first
+            match hooks::add(
+                &config.hooks,
+                &mut apptbook,
+                hooks::Action::Add,
+                &owner,
+                appt,
+            ) {
+                Ok(_) => println!("\nAppointment added successfully"),
+                Err(err) => println!("\n{}", err),
+            }
         } else if input_option == 2 {
             // View appointments option
             if apptbook.is_empty() {
                 println!("Appointment book is empty. Try adding an appointment")
             } else {
-                println!("\nAvailable appointment owners:");
-                for owner in apptbook.keys() {
-                    println!("* {}", owner);
-                }
+                print_owners(&apptbook);
 
                 let mut owner = String::new();
 
@@ -255,25 +369,75 @@ fn main() {
                     .read_line(&mut owner)
                     .expect("Failed to read line");
 
-                // If the owner exists within the HashMap, pretty print their Appointments
-                if let Some(appts) = apptbook.get(owner.trim()) {
-                    for appt in appts {
-                        let formatted_sdt =
-                            appt.start_date_time.format("%m/%d/%Y %H:%M").to_string();
+                // If the owner exists within the book, pretty print their Appointments
+                if let Some(owner) = apptbook.find(&owner) {
+                    let filter = read_filter();
+
+                    // Only upcoming appointments are shown unless history is requested
+                    let now = Local::now().naive_local();
+                    // Appointments the owner is attending are shown along with their own
+                    let appts = apptbook.attending(&owner.handle);
+                    let past = appts
+                        .iter()
+                        .filter(|appt| appt.end_date_time < now && filter.matches(owner, appt))
+                        .count();
+                    let show_past = past > 0
+                        && prompt::confirm(&format!("Show {} past appointment(s) too?", past));
+
+                    let cancelled = appts
+                        .iter()
+                        .filter(|appt| {
+                            appt.is_cancelled()
+                                && (appt.end_date_time >= now || show_past)
+                                && filter.matches(owner, appt)
+                        })
+                        .count();
+                    let show_cancelled = cancelled > 0
+                        && prompt::confirm(&format!(
+                            "Show {} cancelled appointment(s) too?",
+                            cancelled
+                        ));
 
-                        let formatted_edt =
-                            if appt.start_date_time.date() == appt.end_date_time.date() {
-                                appt.end_date_time.format("%H:%M").to_string()
-                            } else {
-                                appt.end_date_time.format("%m/%d/%Y %H:%M").to_string()
-                            };
+                    for appt in appts {
+                        if !filter.matches(owner, appt)
+                            || (appt.end_date_time < now && !show_past)
+                            || (appt.is_cancelled() && !show_cancelled)
+                        {
+                            continue;
+                        }
 
                         println!("\n> {}", appt.description);
-                        println!("| {} to {}", formatted_sdt, formatted_edt);
-                        println!(
-                            "| Duration: {} minutes",
-                            (appt.end_date_time - appt.start_date_time).num_minutes()
-                        );
+                        println!("| {}", appt.formatted_span());
+                        println!("| Duration: {}", appt.formatted_duration());
+
+                        if let Some(location) = &appt.location {
+                            println!("| Location: {}", location);
+                        }
+
+                        if !appt.status.is_confirmed() {
+                            println!("| Status: {}", appt.status.name());
+                        }
+
+                        attendees::print(&apptbook, &owner.handle, appt);
+
+                        if !appt.tags.is_empty() {
+                            println!("| Tags: {}", appt.tags.join(", "));
+                        }
+
+                        if !appt.reminders.is_empty() {
+                            println!(
+                                "| Reminders: {} before",
+                                remind::format_offsets(&appt.reminders)
+                            );
+                        }
+
+                        if let Some(notes) = &appt.notes {
+                            println!("| Notes:");
+
+                            for line in notes.lines() {
+                                println!("|   {}", line);
+                            }
+                        }
                     }
                 } else {
                     println!("There are currently no appointments for that owner");
@@ -284,10 +448,7 @@ fn main() {
             if apptbook.is_empty() {
                 println!("Appointment book is empty. Try adding an appointment")
             } else {
-                println!("\nAvailable appointment owners:");
-                for owner in apptbook.keys() {
-                    println!("* {}", owner);
-                }
+                print_owners(&apptbook);
 
                 let mut owner = String::new();
 
@@ -297,12 +458,13 @@ fn main() {
                     .read_line(&mut owner)
                     .expect("Failed to read line");
 
-                // If the owner exists within the HashMap, ...
-                if apptbook.contains_key(owner.trim()) {
+                // If the owner exists within the book, ...
+                if let Some(owner) = apptbook.find(&owner).map(|owner| owner.handle.clone()) {
                     loop {
                         println!("\n1) Delete all");
                         println!("2) Delete selected");
-                        println!("3) Cancel\n");
+                        println!("3) Delete by date range or filter");
+                        println!("4) Cancel\n");
 
                         print!("Enter an option number: ");
                         io::stdout().flush().unwrap();
@@ -314,9 +476,10 @@ fn main() {
                             .expect("Failed to read line");
 
                         match delete_option.trim() {
-                            "1" => delete_all(owner.trim(), &mut apptbook),
-                            "2" => delete_selected(owner.trim(), &mut apptbook),
-                            "3" => (),
+                            "1" => delete_all(&owner, &mut apptbook, &config.hooks),
+                            "2" => delete_selected(&owner, &mut apptbook, &config.hooks),
+                            "3" => delete_matching(&owner, &mut apptbook, &config.hooks),
+                            "4" => (),
                             _ => {
                                 println!("Invalid option. Must be a valid option number");
                                 continue;
@@ -330,6 +493,35 @@ fn main() {
                 }
             }
         } else if input_option == 4 {
+            // Manage owners option
+            if apptbook.is_empty() {
+                println!("Appointment book is empty. Try adding an appointment")
+            } else {
+                manage_owners(&mut apptbook);
+            }
+        } else if input_option == 5 {
+            // Search appointments option
+            if apptbook.is_empty() {
+                println!("Appointment book is empty. Try adding an appointment")
+            } else {
+                search_appointments(&apptbook);
+            }
+        } else if input_option == 6 || input_option == 7 {
+            // Today's or tomorrow's appointments option
+            let hits = read_owner_hits(&apptbook);
+            let now = Local::now().naive_local();
+            let day = now.date() + chrono::Duration::days(input_option as i64 - 6);
+
+            println!("\n{}", agenda::day(&hits, day, now).trim_end());
+        } else if input_option == 8 {
+            // Next appointment option
+            let hits = read_owner_hits(&apptbook);
+
+            println!(
+                "\n{}",
+                agenda::next(&hits, Local::now().naive_local()).trim_end()
+            );
+        } else if input_option == 9 {
             // Quit option
             println!("\nGoodbye\n");
             break;
@@ -339,122 +531,361 @@ fn main() {
         }
     }
 
-    let serialized = serde_json::to_string(&apptbook).unwrap();
+    // Save book to storage file
+    apptbook.save(book::STORAGE_FILE);
+}
+
+// Load the book, applying the retention policy. Anything archived or purged is saved right away,
+// so the book and archive never both hold an appointment
+fn load_book(config: &Config) -> Book {
+    let mut apptbook = Book::load(book::STORAGE_FILE);
+    let mut archive = archive::load(archive::ARCHIVE_FILE);
+
+    let outcome = archive::apply(
+        &config.retention,
+        &mut apptbook,
+        &mut archive,
+        Local::now().naive_local(),
+    );
 
-    // Save HashMap to storage file
-    fs::write("apptbook.txt", serialized).expect("Failed to write file");
+    if outcome != archive::Outcome::default() {
+        archive.save(archive::ARCHIVE_FILE);
+        apptbook.save(book::STORAGE_FILE);
+    }
+
+    apptbook
 }
 
-fn delete_all(owner: &str, apptbook: &mut HashMap<String, Vec<Appointment>>) {
+fn print_owners(apptbook: &Book) {
+    println!("\nAvailable appointment owners:");
+    for owner in &apptbook.owners {
+        println!("* {} ({})", owner.name, owner.handle);
+    }
+}
+
+// Delete appointments, reporting any a pre-delete hook refused
+fn delete_ids(ids: &[u64], apptbook: &mut Book, hooks: &config::Hooks) {
+    let (deleted, refusals) = hooks::delete_ids(hooks, apptbook, ids);
+
     println!();
 
-    loop {
-        // Confirm deletion of all appointments
-        print!("Delete all appointments for {}? (y or n): ", owner);
-        io::stdout().flush().unwrap();
+    for refusal in &refusals {
+        println!("{}", refusal);
+    }
 
-        let mut confirm = String::new();
-        io::stdin()
-            .read_line(&mut confirm)
-            .expect("Failed to read line");
+    if refusals.is_empty() {
+        println!("Appointments deleted successfully");
+    } else {
+        println!("{} of {} appointment(s) deleted", deleted, ids.len());
+    }
+}
 
-        match confirm.trim().to_lowercase().as_str() {
-            "y" | "yes" => {
-                apptbook.remove(owner);
-                println!("\nAppointments deleted successfully");
-            }
-            "n" | "no" => println!("\nCanceled deleting appointments"),
-            _ => continue,
-        }
+fn delete_all(owner: &str, apptbook: &mut Book, hooks: &config::Hooks) {
+    println!();
 
-        break;
+    let owner = apptbook.find(owner).unwrap();
+
+    // Confirm deletion of all appointments
+    if prompt::confirm(&format!("Delete all appointments for {}?", owner.name)) {
+        let ids: Vec<u64> = owner.appointments.iter().map(|appt| appt.id).collect();
+        delete_ids(&ids, apptbook, hooks);
+    } else {
+        println!("\nCanceled deleting appointments");
     }
 }
 
-fn delete_selected(owner: &str, apptbook: &mut HashMap<String, Vec<Appointment>>) {
-    let appts = apptbook.get_mut(owner).unwrap();
+fn delete_selected(owner: &str, apptbook: &mut Book, hooks: &config::Hooks) {
+    let owner = apptbook.find(owner).unwrap();
+    let appts = &owner.appointments;
     let mut count = 0;
 
+    if appts.is_empty() {
+        println!("There are currently no appointments for that owner");
+        return;
+    }
+
     // Display all owner's appointments
-    for appt in &*appts {
+    for appt in appts {
         count += 1;
 
-        let formatted_sdt = appt.start_date_time.format("%m/%d/%Y %H:%M").to_string();
-
-        let formatted_edt = if appt.start_date_time.date() == appt.end_date_time.date() {
-            appt.end_date_time.format("%H:%M").to_string()
-        } else {
-            appt.end_date_time.format("%m/%d/%Y %H:%M").to_string()
-        };
-
         println!(
-            "\n<{}> {} | {} to {}",
-            count, appt.description, formatted_sdt, formatted_edt
+            "\n<{}> {} | {}",
+            count,
+            appt.description,
+            appt.formatted_span()
         );
     }
 
+    let selected = loop {
+        // Prompt user for which appointments to delete
+        let input =
+            prompt::read_line("\nEnter the numbers of the appointments to delete (e.g. 1,3,5-8): ");
+
+        match prompt::parse_selection(&input, count) {
+            Ok(selected) => break selected,
+            Err(err) => println!("{}", err),
+        }
+    };
+
+    // Display the chosen appointments
+    println!("\nSelected appointments:");
+    for &num in &selected {
+        let appt = &appts[num - 1];
+        println!("<{}> {} | {}", num, appt.description, appt.formatted_span());
+    }
+
+    println!();
+
+    // Confirm deletion of selected appointments
+    if prompt::confirm(&format!(
+        "Delete {} appointment(s) for {}?",
+        selected.len(),
+        owner.name
+    )) {
+        // Remove by ID rather than index, so earlier removals don't shift later ones
+        let ids: Vec<u64> = selected.iter().map(|&num| appts[num - 1].id).collect();
+        delete_ids(&ids, apptbook, hooks);
+    } else {
+        println!("\nCanceled deleting appointments");
+    }
+}
+
+fn delete_matching(owner: &str, apptbook: &mut Book, hooks: &config::Hooks) {
+    println!();
+
+    let filter = read_filter();
+    let handle = apptbook.find(owner).unwrap().handle.clone();
+
+    // Only consider the chosen owner's appointments
+    let matches = |owner: &owner::Owner, appt: &Appointment| {
+        owner.handle == handle && filter.matches(owner, appt)
+    };
+
+    let hits = apptbook.select(matches);
+
+    if hits.is_empty() {
+        println!("No appointments matched the filter");
+        return;
+    }
+
+    // Preview what will be removed
+    search::print_hits(&hits);
+    println!();
+
+    let ids: Vec<u64> = hits.iter().map(|hit| hit.appt.id).collect();
+
+    if prompt::confirm(&format!("Delete these {} appointment(s)?", ids.len())) {
+        delete_ids(&ids, apptbook, hooks);
+    } else {
+        println!("\nCanceled deleting appointments");
+    }
+}
+
+fn manage_owners(apptbook: &mut Book) {
+    print_owners(apptbook);
+
+    let name = prompt::read_line("\nManage owner: ");
+
+    let handle = match apptbook.find(&name) {
+        Some(owner) => owner.handle.clone(),
+        None => {
+            println!("There is currently no owner with that name");
+            return;
+        }
+    };
+
     loop {
-        // Prompt user for which appointment to delete
-        print!("\nEnter the number of the appointment to delete: ");
-        io::stdout().flush().unwrap();
+        println!("\n1) Edit contact info and preferences");
+        println!("2) Rename owner");
+        println!("3) Merge into another owner");
+        println!("4) Remove owner");
+        println!("5) Cancel\n");
+
+        match prompt::read_line("Enter an option number: ").as_str() {
+            "1" => edit_owner(&handle, apptbook),
+            "2" => {
+                let new_name = prompt::read_line("\nEnter the new name: ");
+
+                match apptbook.rename(&handle, &new_name) {
+                    Ok(()) => println!("\nOwner renamed successfully"),
+                    Err(err) => println!("{}", err),
+                }
+            }
+            "3" => {
+                let into = prompt::read_line("\nMerge into owner: ");
 
-        let mut num = String::new();
+                match apptbook.merge(&handle, &into) {
+                    Ok(()) => println!("\nOwners merged successfully"),
+                    Err(err) => println!("{}", err),
+                }
+            }
+            "4" => {
+                println!();
 
-        io::stdin()
-            .read_line(&mut num)
-            .expect("Failed to read line");
+                let name = apptbook.find(&handle).unwrap().name.clone();
 
-        // check if user input is a number
-        let num: usize = match num.trim().parse() {
-            Ok(num) => num,
-            Err(_) => {
-                println!("Invalid input. Must be a valid appointment number");
+                if prompt::confirm(&format!("Remove {} and all their appointments?", name)) {
+                    apptbook.remove_owner(&handle);
+                    println!("\nOwner removed successfully");
+                } else {
+                    println!("\nCanceled removing owner");
+                }
+            }
+            "5" => (),
+            _ => {
+                println!("Invalid option. Must be a valid option number");
                 continue;
             }
-        };
-
-        if num > count || num < 1 {
-            println!("Invalid number. Must be a valid appointment number");
-            continue;
         }
 
+        break;
+    }
+}
+
+// Prompt for an owner, returning their appointments, or everyone's on empty input. If there's
+// no such owner, try again
+fn read_owner_hits(apptbook: &Book) -> Vec<book::Hit<'_>> {
+    if apptbook.owners.len() > 1 {
+        print_owners(apptbook);
         println!();
+    }
+
+    loop {
+        let name = prompt::read_line("Show appointments for (or enter for everyone): ");
+
+        if name.is_empty() {
+            return apptbook.select(|_, _| true);
+        }
+
+        match apptbook.find(&name) {
+            Some(owner) => {
+                let handle = owner.handle.clone();
+                return apptbook.select(|owner, _| owner.handle == handle);
+            }
+            None => println!("There is currently no owner with that name\n"),
+        }
+    }
+}
+
+// Prompt for one of the book's templates by name, or none on empty input. If there's no such
+// template, try again. Without any templates, nothing is asked
+fn read_template(apptbook: &Book) -> Option<templates::Template> {
+    if apptbook.templates.is_empty() {
+        return None;
+    }
+
+    println!("Templates:");
+    for template in &apptbook.templates {
+        println!("* {}", template.summary());
+    }
+
+    loop {
+        let name = prompt::read_line("Enter a template (or enter for none): ");
+
+        if name.is_empty() {
+            return None;
+        }
+
+        match templates::find(apptbook, &name) {
+            Some(template) => return Some(template.clone()),
+            None => println!("There is currently no template with that name\n"),
+        }
+    }
+}
+
+// Prompt for an optional filter expression. If invalid input, try again
+fn read_filter() -> filter::Filter {
+    loop {
+        let input =
+            prompt::read_line("Filter (e.g. after:2026-11-01 duration>60, or enter for all): ");
+
+        match filter::Filter::parse(&input) {
+            Ok(filter) => return filter,
+            Err(err) => println!("{}\n", err),
+        }
+    }
+}
 
+fn search_appointments(apptbook: &Book) {
+    let text = prompt::read_line("\nSearch for: ");
+
+    println!();
+
+    let pattern = if prompt::confirm("Treat the search as a regular expression?") {
+        match search::Pattern::regex(&text) {
+            Ok(pattern) => pattern,
+            Err(err) => {
+                println!("{}", err);
+                return;
+            }
+        }
+    } else {
+        search::Pattern::text(&text)
+    };
+
+    // Prompt for an optional date bound. If invalid input, try again
+    fn read_optional_date(label: &str) -> Option<NaiveDate> {
         loop {
-            // Confirm deletion of selected appointment
-            print!("Delete appointment <{}> for {}? (y or n): ", num, owner);
-            io::stdout().flush().unwrap();
-
-            let mut confirm = String::new();
-            io::stdin()
-                .read_line(&mut confirm)
-                .expect("Failed to read line");
-
-            match confirm.trim().to_lowercase().as_str() {
-                "y" | "yes" => {
-                    // If only one appointment in Vec, delete owner from HashMap
-                    if count == 1 {
-                        apptbook.remove(owner);
-                    } else {
-                        appts.remove(num - 1);
-                    }
-                    println!("\nAppointment deleted successfully");
-                }
-                "n" | "no" => println!("\nCanceled deleting appointment"),
-                _ => continue,
+            let input = prompt::read_line(&format!("{} (mm/dd/yyyy, or enter for any): ", label));
+
+            if input.is_empty() {
+                return None;
             }
 
-            break;
+            match appointment::parse_date(&input) {
+                Ok(date) => return Some(date),
+                Err(err) => println!("{}\n", err),
+            }
         }
+    }
 
-        break;
+    let query = search::Query {
+        pattern,
+        from: read_optional_date("From date"),
+        to: read_optional_date("To date"),
+        tags: tags::parse(&prompt::read_line(
+            "Tags (comma-separated, or enter for any): ",
+        )),
+    };
+
+    let archive = archive::load(archive::ARCHIVE_FILE);
+
+    if !archive.is_empty() && prompt::confirm("Search archived appointments instead?") {
+        search::print_hits(&search::search(&archive, &query));
+    } else {
+        search::print_hits(&search::search(apptbook, &query));
     }
 }
 
+fn edit_owner(handle: &str, apptbook: &mut Book) {
+    let owner = apptbook.find_mut(handle).unwrap();
+
+    println!("\nPress enter to keep the current value, or enter - to clear it\n");
+
+    // Prompt for a new value of an optional field, keeping the old one on empty input
+    fn edit_field(label: &str, field: &mut Option<String>) {
+        let current = field.clone().unwrap_or_else(|| "none".to_string());
+
+        match prompt::read_line(&format!("{} [{}]: ", label, current)).as_str() {
+            "" => (),
+            "-" => *field = None,
+            value => *field = Some(value.to_string()),
+        }
+    }
+
+    edit_field("Email", &mut owner.email);
+    edit_field("Phone", &mut owner.phone);
+    edit_field("Color", &mut owner.preferences.color);
+    edit_field("Time zone", &mut owner.preferences.time_zone);
+
+    println!("\nOwner updated successfully");
+}
+
 //===============================================================================================
 //===== Unit Tests
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn regex_date() {
     let date_re =
         Regex::new(r"^(((0[13-9]|1[012])[/](0[1-9]|[12][0-9]|30)|(0[13578]|1[02])[/]31|02[/](0[1-9]|1[0-9]|2[0-8]))[/][0-9]{4}|02[/]29[/]([0-9]{2}(([2468][048]|[02468][48])|[13579][26])|([13579][26]|[02468][048]|0[0-9]|1[0-6])00))$").unwrap();
@@ -468,6 +899,7 @@ fn regex_date() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn regex_time() {
     let time_re = Regex::new(r"^(0[0-9]|1[0-9]|2[0-3]):[0-5][0-9]$").unwrap();
 
@@ -481,6 +913,7 @@ fn regex_time() {
 }
 
 #[test]
+#[allow(clippy::needless_borrow, clippy::unwrap_or_default)]
 fn serialize_deserialize_apptbook() {
     let start_date_time = "02/02/2020 11:30";
     let end_date_time = "02/02/2020 12:15";
@@ -489,6 +922,7 @@ fn serialize_deserialize_apptbook() {
         description: "Have lunch with Lisa".to_string(),
         start_date_time: NaiveDateTime::parse_from_str(&start_date_time, "%m/%d/%Y %H:%M").unwrap(),
         end_date_time: NaiveDateTime::parse_from_str(&end_date_time, "%m/%d/%Y %H:%M").unwrap(),
+        ..Default::default()
     };
 
     let mut apptbook: HashMap<String, Vec<Appointment>> = HashMap::new();
@@ -516,6 +950,7 @@ fn serialize_deserialize_apptbook() {
 }
 
 #[test]
+#[allow(clippy::vec_init_then_push)]
 fn can_sort_date_time() {
     let dt1 = NaiveDateTime::parse_from_str("02/02/2020 11:30", "%m/%d/%Y %H:%M").unwrap();
     let dt2 = NaiveDateTime::parse_from_str("02/02/2020 11:00", "%m/%d/%Y %H:%M").unwrap();
@@ -535,6 +970,7 @@ fn can_sort_date_time() {
 }
 
 #[test]
+#[allow(clippy::vec_init_then_push)]
 fn sort_by_start_date_time() {
     let sdt1 = NaiveDateTime::parse_from_str("02/15/2020 14:30", "%m/%d/%Y %H:%M").unwrap();
     let edt1 = NaiveDateTime::parse_from_str("02/15/2020 15:00", "%m/%d/%Y %H:%M").unwrap();
@@ -547,16 +983,19 @@ fn sort_by_start_date_time() {
         description: "Do homework".to_string(),
         start_date_time: sdt1,
         end_date_time: edt1,
+        ..Default::default()
     };
     let appt2 = Appointment {
         description: "Do more homework".to_string(),
         start_date_time: sdt2,
         end_date_time: edt2,
+        ..Default::default()
     };
     let appt3 = Appointment {
         description: "Do even more homework".to_string(),
         start_date_time: sdt3,
         end_date_time: edt3,
+        ..Default::default()
     };
 
     let mut vec: Vec<Appointment> = Vec::new();
@@ -577,6 +1016,7 @@ fn sort_by_start_date_time() {
 }
 
 #[test]
+#[allow(clippy::vec_init_then_push)]
 fn sort_by_end_date_time() {
     let sdt1 = NaiveDateTime::parse_from_str("02/07/2020 08:30", "%m/%d/%Y %H:%M").unwrap();
     let edt1 = NaiveDateTime::parse_from_str("02/16/2020 15:00", "%m/%d/%Y %H:%M").unwrap();
@@ -589,16 +1029,19 @@ fn sort_by_end_date_time() {
         description: "Do homework".to_string(),
         start_date_time: sdt1,
         end_date_time: edt1,
+        ..Default::default()
     };
     let appt2 = Appointment {
         description: "Do more homework".to_string(),
         start_date_time: sdt2,
         end_date_time: edt2,
+        ..Default::default()
     };
     let appt3 = Appointment {
         description: "Do even more homework".to_string(),
         start_date_time: sdt3,
         end_date_time: edt3,
+        ..Default::default()
     };
 
     let mut vec: Vec<Appointment> = Vec::new();
@@ -619,6 +1062,7 @@ fn sort_by_end_date_time() {
 }
 
 #[test]
+#[allow(clippy::vec_init_then_push)]
 fn sort_by_description() {
     let sdt1 = NaiveDateTime::parse_from_str("02/25/2020 06:00", "%m/%d/%Y %H:%M").unwrap();
     let edt1 = NaiveDateTime::parse_from_str("02/25/2020 07:00", "%m/%d/%Y %H:%M").unwrap();
@@ -631,16 +1075,19 @@ fn sort_by_description() {
         description: "Do homework".to_string(),
         start_date_time: sdt1,
         end_date_time: edt1,
+        ..Default::default()
     };
     let appt2 = Appointment {
         description: "Do more homework".to_string(),
         start_date_time: sdt2,
         end_date_time: edt2,
+        ..Default::default()
     };
     let appt3 = Appointment {
         description: "Do even more homework".to_string(),
         start_date_time: sdt3,
         end_date_time: edt3,
+        ..Default::default()
     };
 
     let mut vec: Vec<Appointment> = Vec::new();
@@ -697,6 +1144,7 @@ fn time_less_than() {
 }
 
 #[test]
+#[allow(clippy::needless_borrow, clippy::unwrap_or_default)]
 fn remove_owner_from_apptbook() {
     let mut apptbook: HashMap<String, Vec<Appointment>> = HashMap::new();
 
@@ -711,6 +1159,7 @@ fn remove_owner_from_apptbook() {
         start_date_time: NaiveDateTime::parse_from_str(&start_date_time1, "%m/%d/%Y %H:%M")
             .unwrap(),
         end_date_time: NaiveDateTime::parse_from_str(&end_date_time1, "%m/%d/%Y %H:%M").unwrap(),
+        ..Default::default()
     };
 
     let appt2 = Appointment {
@@ -718,6 +1167,7 @@ fn remove_owner_from_apptbook() {
         start_date_time: NaiveDateTime::parse_from_str(&start_date_time2, "%m/%d/%Y %H:%M")
             .unwrap(),
         end_date_time: NaiveDateTime::parse_from_str(&end_date_time2, "%m/%d/%Y %H:%M").unwrap(),
+        ..Default::default()
     };
 
     apptbook
@@ -738,6 +1188,7 @@ fn remove_owner_from_apptbook() {
 }
 
 #[test]
+#[allow(clippy::needless_borrow, clippy::unwrap_or_default)]
 fn remove_appt_from_apptbook_vec() {
     let mut apptbook: HashMap<String, Vec<Appointment>> = HashMap::new();
 
@@ -752,6 +1203,7 @@ fn remove_appt_from_apptbook_vec() {
         start_date_time: NaiveDateTime::parse_from_str(&start_date_time1, "%m/%d/%Y %H:%M")
             .unwrap(),
         end_date_time: NaiveDateTime::parse_from_str(&end_date_time1, "%m/%d/%Y %H:%M").unwrap(),
+        ..Default::default()
     };
 
     let appt2 = Appointment {
@@ -759,6 +1211,7 @@ fn remove_appt_from_apptbook_vec() {
         start_date_time: NaiveDateTime::parse_from_str(&start_date_time2, "%m/%d/%Y %H:%M")
             .unwrap(),
         end_date_time: NaiveDateTime::parse_from_str(&end_date_time2, "%m/%d/%Y %H:%M").unwrap(),
+        ..Default::default()
     };
 
     apptbook
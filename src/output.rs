@@ -0,0 +1,366 @@
+use crate::appointment::DATE_TIME_FORMAT;
+use crate::book::Hit;
+use crate::csv;
+use crate::jsonl::Record;
+use crate::owner::Owner;
+use crate::remind;
+use crate::templates::Template;
+use serde::Serialize;
+
+// Output formats for listings, chosen with --output
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Format {
+    // Tab-separated fields, one item per line, no header
+    Plain,
+    // Columns aligned under a header
+    Table,
+    Csv,
+    // An array of objects. Appointments have the same fields as JSON Lines exports
+    Json,
+}
+
+impl Format {
+    pub fn parse(name: &str) -> Result<Format, String> {
+        match name {
+            "plain" => Ok(Format::Plain),
+            "table" => Ok(Format::Table),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            _ => Err(format!(
+                "Unknown output format: {}. Must be json, csv, table or plain",
+                name
+            )),
+        }
+    }
+}
+
+// JSON form of an owner. Like appointment records, fields may be added but never renamed or
+// removed
+#[derive(Serialize)]
+struct OwnerRecord<'a> {
+    name: &'a str,
+    handle: &'a str,
+    email: Option<&'a str>,
+    phone: Option<&'a str>,
+    appointments: usize,
+}
+
+// JSON form of a tag with how many appointments have it
+#[derive(Serialize)]
+struct TagRecord<'a> {
+    tag: &'a str,
+    appointments: usize,
+}
+
+pub fn appointments(hits: &[Hit], format: Format) -> String {
+    if format == Format::Json {
+        let records: Vec<Record> = hits.iter().map(Record::from_hit).collect();
+        return format!("{}\n", serde_json::to_string_pretty(&records).unwrap());
+    }
+
+    let rows = hits
+        .iter()
+        .map(|hit| {
+            vec![
+                hit.appt.id.to_string(),
+                hit.organizer.name.clone(),
+                hit.appt.description.clone(),
+                hit.appt
+                    .start_date_time
+                    .format(DATE_TIME_FORMAT)
+                    .to_string(),
+                hit.appt.end_date_time.format(DATE_TIME_FORMAT).to_string(),
+                hit.appt.duration_minutes().to_string(),
+            ]
+        })
+        .collect();
+
+    render(
+        &[
+            "id",
+            "owner",
+            "description",
+            "start",
+            "end",
+            "duration_minutes",
+        ],
+        rows,
+        format,
+    )
+}
+
+pub fn owners(owners: &[Owner], format: Format) -> String {
+    if format == Format::Json {
+        let records: Vec<OwnerRecord> = owners
+            .iter()
+            .map(|owner| OwnerRecord {
+                name: &owner.name,
+                handle: &owner.handle,
+                email: owner.email.as_deref(),
+                phone: owner.phone.as_deref(),
+                appointments: owner.appointments.len(),
+            })
+            .collect();
+        return format!("{}\n", serde_json::to_string_pretty(&records).unwrap());
+    }
+
+    let rows = owners
+        .iter()
+        .map(|owner| {
+            vec![
+                owner.handle.clone(),
+                owner.name.clone(),
+                owner.email.clone().unwrap_or_default(),
+                owner.phone.clone().unwrap_or_default(),
+                owner.appointments.len().to_string(),
+            ]
+        })
+        .collect();
+
+    render(
+        &["handle", "name", "email", "phone", "appointments"],
+        rows,
+        format,
+    )
+}
+
+pub fn tags(counts: &[(String, usize)], format: Format) -> String {
+    if format == Format::Json {
+        let records: Vec<TagRecord> = counts
+            .iter()
+            .map(|(tag, appointments)| TagRecord {
+                tag,
+                appointments: *appointments,
+            })
+            .collect();
+        return format!("{}\n", serde_json::to_string_pretty(&records).unwrap());
+    }
+
+    let rows = counts
+        .iter()
+        .map(|(tag, appointments)| vec![tag.clone(), appointments.to_string()])
+        .collect();
+
+    render(&["tag", "appointments"], rows, format)
+}
+
+pub fn templates(templates: &[Template], format: Format) -> String {
+    if format == Format::Json {
+        return format!("{}\n", serde_json::to_string_pretty(templates).unwrap());
+    }
+
+    let rows = templates
+        .iter()
+        .map(|template| {
+            vec![
+                template.name.clone(),
+                template.description.clone(),
+                template.duration_minutes.to_string(),
+                template.tags.join(", "),
+                remind::format_offsets(&template.reminders),
+            ]
+        })
+        .collect();
+
+    render(
+        &[
+            "name",
+            "description",
+            "duration_minutes",
+            "tags",
+            "reminders",
+        ],
+        rows,
+        format,
+    )
+}
+
+// Lay out rows of fields as plain, table or CSV text
+fn render(headers: &[&str], rows: Vec<Vec<String>>, format: Format) -> String {
+    let mut out = String::new();
+
+    if format == Format::Csv {
+        out.push_str(&csv::record(headers, ','));
+
+        for row in &rows {
+            let fields: Vec<&str> = row.iter().map(String::as_str).collect();
+            out.push_str(&csv::record(&fields, ','));
+        }
+
+        return out;
+    }
+
+    // Tabs and line breaks would split a field, so flatten them to spaces
+    let rows: Vec<Vec<String>> = rows
+        .into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|field| field.replace(['\t', '\n', '\r'], " "))
+                .collect()
+        })
+        .collect();
+
+    if format == Format::Plain {
+        for row in &rows {
+            out.push_str(&row.join("\t"));
+            out.push('\n');
+        }
+
+        return out;
+    }
+
+    let mut widths: Vec<usize> = headers.iter().map(|header| header.len()).collect();
+
+    for row in &rows {
+        for (width, field) in widths.iter_mut().zip(row) {
+            *width = (*width).max(field.chars().count());
+        }
+    }
+
+    let mut line = |fields: &[&str]| {
+        let padded: Vec<String> = fields
+            .iter()
+            .zip(&widths)
+            .map(|(field, width)| format!("{:width$}", field, width = width))
+            .collect();
+
+        out.push_str(padded.join("  ").trim_end());
+        out.push('\n');
+    };
+
+    let upper: Vec<String> = headers.iter().map(|header| header.to_uppercase()).collect();
+    line(&upper.iter().map(String::as_str).collect::<Vec<_>>());
+
+    for row in &rows {
+        line(&row.iter().map(String::as_str).collect::<Vec<_>>());
+    }
+
+    out
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn sample_book() -> crate::book::Book {
+    let mut apptbook = crate::book::Book::sample(&[
+        (
+            "Alice",
+            "Review, draft",
+            "10/18/2026 10:00",
+            "10/18/2026 11:00",
+        ),
+        ("Bob", "Lunch", "10/18/2026 12:00", "10/18/2026 12:30"),
+    ]);
+    apptbook.find_mut("bob").unwrap().email = Some("bob@example.com".to_string());
+
+    apptbook
+}
+
+#[test]
+fn appointments_json_schema() {
+    let apptbook = sample_book();
+    let output = appointments(&apptbook.select(|_, _| true), Format::Json);
+    let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+
+    assert_eq!(
+        value[1],
+        serde_json::json!({
+            "id": 2,
+            "owner": "Bob",
+            "owner_handle": "bob",
+            "description": "Lunch",
+            "start": "2026-10-18T12:00:00",
+            "end": "2026-10-18T12:30:00",
+            "duration_minutes": 30
+        })
+    );
+    assert_eq!(appointments(&[], Format::Json), "[]\n");
+}
+
+#[test]
+fn owners_json_schema() {
+    let apptbook = sample_book();
+    let value: serde_json::Value =
+        serde_json::from_str(&owners(&apptbook.owners, Format::Json)).unwrap();
+
+    assert_eq!(
+        value,
+        serde_json::json!([
+            {"name": "Alice", "handle": "alice", "email": null, "phone": null, "appointments": 1},
+            {"name": "Bob", "handle": "bob", "email": "bob@example.com", "phone": null, "appointments": 1}
+        ])
+    );
+}
+
+#[test]
+fn appointments_as_text() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+
+    assert_eq!(
+        appointments(&hits, Format::Table),
+        "ID  OWNER  DESCRIPTION    START             END               DURATION_MINUTES\n\
+         1   Alice  Review, draft  10/18/2026 10:00  10/18/2026 11:00  60\n\
+         2   Bob    Lunch          10/18/2026 12:00  10/18/2026 12:30  30\n"
+    );
+    assert_eq!(
+        appointments(&hits[1..], Format::Plain),
+        "2\tBob\tLunch\t10/18/2026 12:00\t10/18/2026 12:30\t30\n"
+    );
+    assert_eq!(
+        appointments(&hits[..1], Format::Csv),
+        "id,owner,description,start,end,duration_minutes\n\
+         1,Alice,\"Review, draft\",10/18/2026 10:00,10/18/2026 11:00,60\n"
+    );
+    assert!(Format::parse("yaml").is_err());
+}
+
+#[test]
+fn attendee_hits_name_the_organizer() {
+    let mut apptbook = sample_book();
+    apptbook.owners[0].appointments[0]
+        .attendees
+        .push(crate::attendees::Attendee::new("bob"));
+
+    let hits = apptbook.select_participating(|owner, _| owner.handle == "bob");
+
+    assert_eq!(
+        appointments(&hits, Format::Plain),
+        "1\tAlice\tReview, draft\t10/18/2026 10:00\t10/18/2026 11:00\t60\n\
+         2\tBob\tLunch\t10/18/2026 12:00\t10/18/2026 12:30\t30\n"
+    );
+}
+
+#[test]
+fn tags_and_templates_as_text() {
+    let counts = vec![("review".to_string(), 2), ("lunch".to_string(), 1)];
+
+    assert_eq!(
+        tags(&counts, Format::Csv),
+        "tag,appointments\nreview,2\nlunch,1\n"
+    );
+    let value: serde_json::Value = serde_json::from_str(&tags(&counts, Format::Json)).unwrap();
+    assert_eq!(
+        value[1],
+        serde_json::json!({"tag": "lunch", "appointments": 1})
+    );
+
+    let list = vec![Template::new("Follow-up", "Follow-up visit", "20", "clinic", "15m").unwrap()];
+
+    assert_eq!(
+        templates(&list, Format::Plain),
+        "Follow-up\tFollow-up visit\t20\tclinic\t15m\n"
+    );
+    let value: serde_json::Value = serde_json::from_str(&templates(&list, Format::Json)).unwrap();
+    assert_eq!(
+        value,
+        serde_json::json!([{
+            "name": "Follow-up",
+            "description": "Follow-up visit",
+            "duration_minutes": 20,
+            "tags": ["clinic"],
+            "reminders": [15]
+        }])
+    );
+}
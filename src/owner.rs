@@ -0,0 +1,84 @@
+use crate::appointment::Appointment;
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct Preferences {
+    // Color used when rendering the owner's appointments, e.g. "#3366cc"
+    pub color: Option<String>,
+    // IANA time zone name, e.g. "America/Los_Angeles"
+    pub time_zone: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Owner {
+    pub name: String,
+    pub handle: String,
+    #[serde(default)]
+    pub email: Option<String>,
+    #[serde(default)]
+    pub phone: Option<String>,
+    #[serde(default)]
+    pub preferences: Preferences,
+    #[serde(default)]
+    pub appointments: Vec<Appointment>,
+}
+
+impl Owner {
+    pub fn new(name: &str) -> Owner {
+        Owner {
+            name: name.split_whitespace().collect::<Vec<_>>().join(" "),
+            handle: handle_for(name),
+            email: None,
+            phone: None,
+            preferences: Preferences::default(),
+            appointments: Vec::new(),
+        }
+    }
+
+    // An owner matches if the input equals either their display name or their handle, ignoring
+    // case and surrounding/repeated whitespace
+    pub fn matches(&self, input: &str) -> bool {
+        let input = normalize(input);
+
+        !input.is_empty() && (input == normalize(&self.name) || input == self.handle)
+    }
+}
+
+// Lowercase the name and collapse runs of whitespace into a single space, so "Alice " and
+// "alice" compare equal
+pub fn normalize(name: &str) -> String {
+    name.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+// Owners are found by name, so a name must have more than whitespace in it
+pub fn check_name(name: &str) -> Result<(), String> {
+    if normalize(name).is_empty() {
+        return Err("Owner cannot be empty".to_string());
+    }
+
+    Ok(())
+}
+
+// Derive a handle from a display name: normalized, with spaces replaced by dashes
+pub fn handle_for(name: &str) -> String {
+    normalize(name).replace(' ', "-")
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn owner_matches_ignoring_case_and_whitespace() {
+    let owner = Owner::new("Alice  Smith ");
+
+    assert_eq!(owner.name, "Alice Smith");
+    assert_eq!(owner.handle, "alice-smith");
+    assert!(owner.matches("alice smith"));
+    assert!(owner.matches("  ALICE   SMITH"));
+    assert!(owner.matches("alice-smith"));
+    assert!(!owner.matches("alice"));
+    assert!(!owner.matches("   "));
+}
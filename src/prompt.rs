@@ -0,0 +1,116 @@
+use std::io;
+use std::io::Write;
+
+// Print a prompt and read back one trimmed line of input
+pub fn read_line(prompt: &str) -> String {
+    print!("{}", prompt);
+    io::stdout().flush().unwrap();
+
+    let mut input = String::new();
+    io::stdin()
+        .read_line(&mut input)
+        .expect("Failed to read line");
+
+    input.trim().to_string()
+}
+
+// Print a prompt and read lines until an empty one, for text that may span several lines. Returns
+// None if the first line is empty
+pub fn read_lines(prompt: &str) -> Option<String> {
+    println!("{}", prompt);
+
+    let mut lines = Vec::new();
+
+    loop {
+        let mut input = String::new();
+
+        // Stop at the end of input too
+        if io::stdin()
+            .read_line(&mut input)
+            .expect("Failed to read line")
+            == 0
+        {
+            break;
+        }
+
+        let line = input.trim_end();
+
+        if line.trim().is_empty() {
+            break;
+        }
+
+        lines.push(line.to_string());
+    }
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(lines.join("\n"))
+    }
+}
+
+// Ask a yes or no question until the user answers with one of them
+pub fn confirm(prompt: &str) -> bool {
+    loop {
+        match read_line(&format!("{} (y or n): ", prompt))
+            .to_lowercase()
+            .as_str()
+        {
+            "y" | "yes" => return true,
+            "n" | "no" => return false,
+            _ => continue,
+        }
+    }
+}
+
+// Parse a selection of appointment numbers such as "1,3,5-8" into a sorted list without
+// duplicates. Numbers must be between 1 and count
+pub fn parse_selection(input: &str, count: usize) -> Result<Vec<usize>, String> {
+    let mut selected = Vec::new();
+
+    for part in input.split(',').map(str::trim) {
+        let (first, last) = match part.find('-') {
+            Some(index) => (part[..index].trim(), part[index + 1..].trim()),
+            None => (part, part),
+        };
+
+        let (first, last): (usize, usize) = match (first.parse(), last.parse()) {
+            (Ok(first), Ok(last)) => (first, last),
+            _ => {
+                return Err(format!(
+                    "Invalid input. Must be appointment numbers or ranges: {}",
+                    part
+                ))
+            }
+        };
+
+        if first < 1 || last > count || first > last {
+            return Err(format!(
+                "Invalid number. Must be valid appointment numbers: {}",
+                part
+            ));
+        }
+
+        selected.extend(first..=last);
+    }
+
+    selected.sort_unstable();
+    selected.dedup();
+
+    Ok(selected)
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn parse_selection_lists_and_ranges() {
+    assert_eq!(parse_selection("1,3,5-8", 8), Ok(vec![1, 3, 5, 6, 7, 8]));
+    assert_eq!(parse_selection(" 4 , 2-3, 3 ", 4), Ok(vec![2, 3, 4]));
+    assert_eq!(parse_selection("2", 2), Ok(vec![2]));
+    assert!(parse_selection("0", 3).is_err());
+    assert!(parse_selection("1-4", 3).is_err());
+    assert!(parse_selection("3-1", 3).is_err());
+    assert!(parse_selection("1,,2", 3).is_err());
+    assert!(parse_selection("a", 3).is_err());
+}
@@ -0,0 +1,334 @@
+use crate::book::Hit;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::process::Command;
+
+// The furthest ahead of an appointment a reminder can be, about a century
+pub const MAX_OFFSET_MINUTES: i64 = 100 * 366 * 24 * 60;
+
+// Reminders that already fired, so restarting doesn't repeat them
+pub const FIRED_FILE: &str = "apptbook_reminders.txt";
+
+// Where due reminders are sent
+pub enum Sink {
+    Stdout,
+    // Shell command run with the reminder's title and message as arguments, e.g. notify-send
+    Command(String),
+    // Mailbox file in mbox format that mail clients can read, e.g. /var/mail/alice
+    Mail(String),
+}
+
+// A reminder that's due, for an appointment that hasn't started yet
+pub struct Due<'a> {
+    pub hit: &'a Hit<'a>,
+    // Minutes before the start
+    pub offset: i64,
+    key: String,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct Fired {
+    keys: BTreeSet<String>,
+}
+
+impl Fired {
+    pub fn load(path: &str) -> Fired {
+        match fs::read_to_string(path) {
+            Ok(contents) => {
+                serde_json::from_str(&contents).expect("Failed to parse reminders file")
+            }
+            Err(_) => Fired::default(),
+        }
+    }
+
+    pub fn save(&self, path: &str) {
+        let serialized = serde_json::to_string(self).unwrap();
+        fs::write(path, serialized).expect("Failed to write to reminders file");
+    }
+
+    pub fn mark(&mut self, due: &Due) {
+        self.keys.insert(due.key.clone());
+    }
+
+    // Forget reminders of appointments that have started or are gone, which can't be due again
+    pub fn prune(&mut self, hits: &[Hit], now: NaiveDateTime) {
+        let upcoming: BTreeSet<String> = hits
+            .iter()
+            .filter(|hit| hit.appt.start_date_time > now)
+            .flat_map(|hit| {
+                hit.appt
+                    .reminders
+                    .iter()
+                    .map(move |&offset| key(hit, offset))
+            })
+            .collect();
+
+        self.keys.retain(|key| upcoming.contains(key));
+    }
+}
+
+// Identifies a reminder by appointment, start and offset, so moving an appointment reminds again
+fn key(hit: &Hit, offset: i64) -> String {
+    format!(
+        "{}@{}-{}",
+        hit.appt.id,
+        hit.appt.start_date_time.format("%Y%m%dT%H%M"),
+        offset
+    )
+}
+
+// Reminders that are due at now and haven't fired. Reminders missed while nothing was checking
+// are still due until the appointment starts. Cancelled appointments have no reminders
+pub fn due<'a>(hits: &'a [Hit<'a>], fired: &Fired, now: NaiveDateTime) -> Vec<Due<'a>> {
+    let mut due = Vec::new();
+
+    for hit in hits {
+        let start = hit.appt.start_date_time;
+
+        if start <= now || hit.appt.is_cancelled() {
+            continue;
+        }
+
+        for &offset in &hit.appt.reminders {
+            let key = key(hit, offset);
+
+            if start - Duration::minutes(offset) <= now && !fired.keys.contains(&key) {
+                due.push(Due { hit, offset, key });
+            }
+        }
+    }
+
+    due.sort_by_key(|due| (due.hit.appt.start_date_time, -due.offset));
+    due
+}
+
+// Parse reminder offsets like "15m, 1h, 1d" into minutes before the start. Plain numbers are
+// minutes, and "none" or an empty string means no reminders
+pub fn parse_offsets(input: &str) -> Result<Vec<i64>, String> {
+    let input = input.trim();
+
+    if input.is_empty() || input.eq_ignore_ascii_case("none") {
+        return Ok(Vec::new());
+    }
+
+    let mut offsets = Vec::new();
+
+    for part in input.split(',').map(str::trim) {
+        let invalid = || format!("Invalid reminder. Must be like 15m, 2h, 1d or 1w: {}", part);
+
+        let (number, unit) = match part.find(|c: char| !c.is_ascii_digit()) {
+            Some(index) => (&part[..index], part[index..].trim()),
+            None => (part, "m"),
+        };
+
+        let number: i64 = number.parse().map_err(|_| invalid())?;
+
+        let unit_minutes = match unit.to_lowercase().as_str() {
+            "m" | "min" | "mins" | "minute" | "minutes" => 1,
+            "h" | "hour" | "hours" => 60,
+            "d" | "day" | "days" => 24 * 60,
+            "w" | "week" | "weeks" => 7 * 24 * 60,
+            _ => return Err(invalid()),
+        };
+
+        // Offsets are subtracted from start times, so they have to stay within what dates can
+        // reach
+        let minutes = number
+            .checked_mul(unit_minutes)
+            .filter(|&minutes| minutes <= MAX_OFFSET_MINUTES)
+            .ok_or_else(invalid)?;
+
+        if !offsets.contains(&minutes) {
+            offsets.push(minutes);
+        }
+    }
+
+    offsets.sort_unstable();
+    Ok(offsets)
+}
+
+// Format an offset in the largest unit that divides it, e.g. "90m", "2h" or "1d"
+pub fn format_offset(minutes: i64) -> String {
+    if minutes > 0 && minutes % (7 * 24 * 60) == 0 {
+        format!("{}w", minutes / (7 * 24 * 60))
+    } else if minutes > 0 && minutes % (24 * 60) == 0 {
+        format!("{}d", minutes / (24 * 60))
+    } else if minutes > 0 && minutes % 60 == 0 {
+        format!("{}h", minutes / 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+pub fn format_offsets(offsets: &[i64]) -> String {
+    let formatted: Vec<String> = offsets
+        .iter()
+        .map(|&offset| format_offset(offset))
+        .collect();
+    formatted.join(", ")
+}
+
+fn title(due: &Due) -> String {
+    format!("Reminder: {}", due.hit.appt.description.replace('\n', " "))
+}
+
+fn message(due: &Due, now: NaiveDateTime) -> String {
+    format!(
+        "{} ({}) starts {}, {}",
+        due.hit.appt.description.replace('\n', " "),
+        due.hit.owner.name,
+        crate::agenda::countdown(due.hit.appt.start_date_time - now),
+        due.hit.appt.formatted_span()
+    )
+}
+
+// Send a due reminder
+pub fn emit(sink: &Sink, due: &Due, now: NaiveDateTime) -> Result<(), String> {
+    let title = title(due);
+    let message = message(due, now);
+
+    match sink {
+        Sink::Stdout => {
+            println!("{}: {}", title, message);
+            Ok(())
+        }
+        Sink::Command(command) => {
+            // Arguments are passed separately so the shell doesn't interpret them
+            let status = Command::new("sh")
+                .arg("-c")
+                .arg(format!("{} \"$@\"", command))
+                .arg("sh")
+                .arg(&title)
+                .arg(&message)
+                .status()
+                .map_err(|err| format!("Failed to run {}: {}", command, err))?;
+
+            if status.success() {
+                Ok(())
+            } else {
+                Err(format!("{} failed with {}", command, status))
+            }
+        }
+        Sink::Mail(path) => {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|err| format!("Failed to open {}: {}", path, err))?;
+
+            file.write_all(mail(due, &title, &message, Local::now()).as_bytes())
+                .map_err(|err| format!("Failed to write to {}: {}", path, err))
+        }
+    }
+}
+
+// A message in mbox format, addressed to the owner's email if they have one
+fn mail(due: &Due, title: &str, message: &str, date: DateTime<Local>) -> String {
+    let to = match &due.hit.owner.email {
+        Some(email) => format!("{} <{}>", due.hit.owner.name, email),
+        None => due.hit.owner.name.clone(),
+    };
+
+    // Lines starting with "From " would begin a new message
+    let body: Vec<String> = message
+        .lines()
+        .map(|line| {
+            if line.starts_with("From ") {
+                format!(">{}", line)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+
+    format!(
+        "From apptbook {}\nFrom: apptbook\nTo: {}\nSubject: {}\nDate: {}\n\n{}\n\n",
+        date.format("%a %b %e %H:%M:%S %Y"),
+        to,
+        title,
+        date.to_rfc2822(),
+        body.join("\n")
+    )
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn sample_book() -> crate::book::Book {
+    let mut apptbook = crate::book::Book::sample(&[
+        ("Alice", "Review", "10/19/2026 09:00", "10/19/2026 10:00"),
+        ("Bob", "Lunch", "10/19/2026 12:00", "10/19/2026 13:00"),
+    ]);
+    apptbook.find_mut("alice").unwrap().appointments[0].reminders = vec![15, 24 * 60];
+
+    apptbook
+}
+
+#[test]
+fn due_reminders_fire_once() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+    let mut fired = Fired::default();
+
+    let day_before = NaiveDate::from_ymd(2026, 10, 18).and_hms(9, 30, 0);
+    let due_now = due(&hits, &fired, day_before);
+    assert_eq!(due_now.len(), 1);
+    assert_eq!(due_now[0].offset, 24 * 60);
+    assert_eq!(
+        message(&due_now[0], day_before),
+        "Review (Alice) starts in 23 hours 30 minutes, 10/19/2026 09:00 to 10:00"
+    );
+
+    fired.mark(&due_now[0]);
+    assert!(due(&hits, &fired, day_before).is_empty());
+
+    // Both are past due, but only the one that hasn't fired is sent
+    let soon = NaiveDate::from_ymd(2026, 10, 19).and_hms(8, 50, 0);
+    let due_soon = due(&hits, &fired, soon);
+    assert_eq!(due_soon.len(), 1);
+    assert_eq!(due_soon[0].offset, 15);
+
+    // Nothing is due once the appointment started, and what fired is forgotten
+    let started = NaiveDate::from_ymd(2026, 10, 19).and_hms(9, 0, 0);
+    assert!(due(&hits, &Fired::default(), started).is_empty());
+    fired.prune(&hits, started);
+    assert!(fired.keys.is_empty());
+}
+
+#[test]
+fn parse_and_format_offsets() {
+    assert_eq!(
+        parse_offsets("1d, 15m,2h, 30").unwrap(),
+        vec![15, 30, 120, 24 * 60]
+    );
+    assert_eq!(parse_offsets("none").unwrap(), Vec::<i64>::new());
+    assert_eq!(parse_offsets("1 week").unwrap(), vec![7 * 24 * 60]);
+    assert!(parse_offsets("soon").is_err());
+    assert!(parse_offsets("5y").is_err());
+    assert!(parse_offsets("9223372036854775807w").is_err());
+    assert!(parse_offsets("99999999999m").is_err());
+    assert_eq!(
+        format_offsets(&[15, 90, 120, 24 * 60, 7 * 24 * 60]),
+        "15m, 90m, 2h, 1d, 1w"
+    );
+}
+
+#[test]
+fn mail_is_mbox_message() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+    let now = NaiveDate::from_ymd(2026, 10, 19).and_hms(8, 45, 0);
+    let due_now = due(&hits, &Fired::default(), now);
+    let date = Local.from_local_datetime(&now).unwrap();
+
+    let mail = mail(&due_now[0], &title(&due_now[0]), "From here", date);
+
+    assert!(mail.starts_with("From apptbook Mon Oct 19 08:45:00 2026\nFrom: apptbook\nTo: Alice\n"));
+    assert!(mail.contains("\nSubject: Reminder: Review\n"));
+    assert!(mail.ends_with("\n\n>From here\n\n"));
+}
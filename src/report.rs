@@ -0,0 +1,404 @@
+use crate::agenda;
+use crate::book::Hit;
+use chrono::prelude::*;
+use chrono::Duration;
+use serde::Serialize;
+use std::fmt::Write;
+
+const WEEKDAYS: [&str; 7] = ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"];
+
+// How many of the busiest weekdays and hours the text report lists
+const BUSIEST: usize = 3;
+
+// Hours of the working day, Monday to Friday, that bookings are measured against
+#[derive(Clone, Copy)]
+pub struct WorkingHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+}
+
+impl WorkingHours {
+    // Parse hours like "09:00-17:00"
+    pub fn parse(input: &str) -> Result<WorkingHours, String> {
+        let invalid = || {
+            format!(
+                "Invalid working hours. Required format: hh:mm-hh:mm: {}",
+                input
+            )
+        };
+
+        let (start, end) = match input.split_once('-') {
+            Some((start, end)) => (start.trim(), end.trim()),
+            None => return Err(invalid()),
+        };
+
+        let start = NaiveTime::parse_from_str(start, "%H:%M").map_err(|_| invalid())?;
+        let end = NaiveTime::parse_from_str(end, "%H:%M").map_err(|_| invalid())?;
+
+        if end <= start {
+            return Err("Invalid working hours. The end must be after the start".to_string());
+        }
+
+        Ok(WorkingHours { start, end })
+    }
+}
+
+impl Default for WorkingHours {
+    fn default() -> WorkingHours {
+        WorkingHours {
+            start: NaiveTime::from_hms(9, 0, 0),
+            end: NaiveTime::from_hms(17, 0, 0),
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct Count {
+    name: String,
+    appointments: usize,
+}
+
+#[derive(Serialize)]
+pub struct Gap {
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    minutes: i64,
+}
+
+// Statistics for one owner. Serialized as the JSON report, so fields may be added but never
+// renamed or removed
+#[derive(Serialize)]
+pub struct OwnerStats {
+    name: String,
+    handle: String,
+    appointments: usize,
+    total_minutes: i64,
+    average_minutes: i64,
+    // Appointments starting on each weekday, Monday first
+    weekdays: Vec<Count>,
+    // Appointments starting in each hour of the day, midnight first
+    hours: Vec<Count>,
+    // Longest free time between appointments within the range
+    longest_gap: Option<Gap>,
+    working_minutes: i64,
+    booked_working_minutes: i64,
+    booked_working_percent: f64,
+}
+
+#[derive(Serialize)]
+pub struct Report {
+    from: NaiveDate,
+    to: NaiveDate,
+    working_hours: String,
+    owners: Vec<OwnerStats>,
+}
+
+// Summarize the appointments in the days from and to, inclusive, per owner. Only the parts of
+// appointments inside the range count towards durations, gaps and working hours booked, so
+// appointments spanning its ends aren't counted in full. Cancelled appointments don't count, and
+// neither do all-day ones, which mark days rather than book time
+pub fn report(hits: &[Hit], from: NaiveDate, to: NaiveDate, hours: WorkingHours) -> Report {
+    let hits: Vec<&Hit> = hits
+        .iter()
+        .filter(|hit| !hit.appt.is_cancelled() && !hit.appt.all_day)
+        .collect();
+    let range_start = from.and_hms(0, 0, 0);
+    let range_end = to.succ().and_hms(0, 0, 0);
+
+    let mut owners: Vec<OwnerStats> = Vec::new();
+    let mut handles: Vec<&str> = Vec::new();
+
+    for hit in &hits {
+        if !handles.contains(&hit.owner.handle.as_str()) {
+            handles.push(&hit.owner.handle);
+        }
+    }
+
+    for handle in handles {
+        // Clip each appointment to the range, dropping those outside it
+        let mut intervals: Vec<(NaiveDateTime, NaiveDateTime)> = hits
+            .iter()
+            .filter(|hit| hit.owner.handle == handle)
+            .filter(|hit| {
+                hit.appt.start_date_time < range_end
+                    && (hit.appt.end_date_time > range_start
+                        || hit.appt.start_date_time >= range_start)
+            })
+            .map(|hit| {
+                (
+                    hit.appt.start_date_time.max(range_start),
+                    hit.appt.end_date_time.min(range_end),
+                )
+            })
+            .collect();
+
+        if intervals.is_empty() {
+            continue;
+        }
+
+        intervals.sort();
+
+        let owner = hits
+            .iter()
+            .find(|hit| hit.owner.handle == handle)
+            .unwrap()
+            .owner;
+
+        let count = intervals.len();
+        let total_minutes: i64 = intervals
+            .iter()
+            .map(|(start, end)| (*end - *start).num_minutes())
+            .sum();
+
+        let mut weekdays: Vec<Count> = WEEKDAYS
+            .iter()
+            .map(|name| Count {
+                name: name.to_string(),
+                appointments: 0,
+            })
+            .collect();
+        let mut by_hour: Vec<Count> = (0..24)
+            .map(|hour| Count {
+                name: format!("{:02}:00", hour),
+                appointments: 0,
+            })
+            .collect();
+
+        for (start, _) in &intervals {
+            weekdays[start.weekday().num_days_from_monday() as usize].appointments += 1;
+            by_hour[start.hour() as usize].appointments += 1;
+        }
+
+        // Overlapping appointments are merged, so double bookings don't count twice
+        let mut merged: Vec<(NaiveDateTime, NaiveDateTime)> = Vec::new();
+
+        for &(start, end) in &intervals {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+
+        let longest_gap = merged
+            .windows(2)
+            .map(|pair| (pair[0].1, pair[1].0))
+            .max_by_key(|(start, end)| *end - *start)
+            .map(|(start, end)| Gap {
+                start,
+                end,
+                minutes: (end - start).num_minutes(),
+            });
+
+        let mut working_minutes = 0;
+        let mut booked_working_minutes = 0;
+        let mut day = from;
+
+        while day <= to {
+            if day.weekday().num_days_from_monday() < 5 {
+                let (work_start, work_end) = (day.and_time(hours.start), day.and_time(hours.end));
+                working_minutes += (work_end - work_start).num_minutes();
+
+                for &(start, end) in &merged {
+                    let (start, end) = (start.max(work_start), end.min(work_end));
+
+                    if start < end {
+                        booked_working_minutes += (end - start).num_minutes();
+                    }
+                }
+            }
+
+            day = day.succ();
+        }
+
+        let booked_working_percent = if working_minutes == 0 {
+            0.0
+        } else {
+            // Rounded to one decimal place
+            (booked_working_minutes as f64 * 1000.0 / working_minutes as f64).round() / 10.0
+        };
+
+        owners.push(OwnerStats {
+            name: owner.name.clone(),
+            handle: owner.handle.clone(),
+            appointments: count,
+            total_minutes,
+            average_minutes: (total_minutes as f64 / count as f64).round() as i64,
+            weekdays,
+            hours: by_hour,
+            longest_gap,
+            working_minutes,
+            booked_working_minutes,
+            booked_working_percent,
+        });
+    }
+
+    Report {
+        from,
+        to,
+        working_hours: format!(
+            "{}-{}",
+            hours.start.format("%H:%M"),
+            hours.end.format("%H:%M")
+        ),
+        owners,
+    }
+}
+
+pub fn json(report: &Report) -> String {
+    serde_json::to_string_pretty(report).unwrap() + "\n"
+}
+
+pub fn text(report: &Report) -> String {
+    let mut out = String::new();
+    writeln!(
+        out,
+        "Report for {} to {}",
+        report.from.format("%m/%d/%Y"),
+        report.to.format("%m/%d/%Y")
+    )
+    .unwrap();
+
+    if report.owners.is_empty() {
+        out.push_str("\nNo appointments in the range\n");
+    }
+
+    // The most common names first, leaving out those without appointments
+    let busiest = |counts: &[Count]| {
+        let mut counts: Vec<&Count> = counts
+            .iter()
+            .filter(|count| count.appointments > 0)
+            .collect();
+        counts.sort_by_key(|count| std::cmp::Reverse(count.appointments));
+
+        let listed: Vec<String> = counts
+            .iter()
+            .take(BUSIEST)
+            .map(|count| format!("{} ({})", count.name, count.appointments))
+            .collect();
+        listed.join(", ")
+    };
+
+    for stats in &report.owners {
+        writeln!(out, "\n{} ({})", stats.name, stats.handle).unwrap();
+        writeln!(out, "  Appointments:          {}", stats.appointments).unwrap();
+        writeln!(
+            out,
+            "  Total duration:        {} ({} minutes)",
+            agenda::length(Duration::minutes(stats.total_minutes)),
+            stats.total_minutes
+        )
+        .unwrap();
+        writeln!(
+            out,
+            "  Average duration:      {} minutes",
+            stats.average_minutes
+        )
+        .unwrap();
+        writeln!(out, "  Busiest weekdays:      {}", busiest(&stats.weekdays)).unwrap();
+        writeln!(out, "  Busiest hours:         {}", busiest(&stats.hours)).unwrap();
+
+        match &stats.longest_gap {
+            Some(gap) => writeln!(
+                out,
+                "  Longest gap:           {}, {} to {}",
+                agenda::length(Duration::minutes(gap.minutes)),
+                gap.start.format("%m/%d/%Y %H:%M"),
+                gap.end.format("%m/%d/%Y %H:%M")
+            )
+            .unwrap(),
+            None => writeln!(out, "  Longest gap:           none").unwrap(),
+        }
+
+        writeln!(
+            out,
+            "  Working hours booked:  {}% of {} hours (Mon-Fri {})",
+            stats.booked_working_percent,
+            stats.working_minutes as f64 / 60.0,
+            report.working_hours
+        )
+        .unwrap();
+    }
+
+    out
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn sample_book() -> crate::book::Book {
+    crate::book::Book::sample(&[
+        // Monday to Wednesday, with a double booking on Monday and a weekend appointment
+        ("Alice", "Review", "10/19/2026 09:00", "10/19/2026 11:00"),
+        ("Alice", "Call", "10/19/2026 10:00", "10/19/2026 10:30"),
+        ("Alice", "Workshop", "10/21/2026 13:00", "10/21/2026 17:30"),
+        ("Alice", "Hike", "10/24/2026 09:00", "10/24/2026 12:00"),
+        // Ends inside the range, so only its last hour counts
+        ("Alice", "Flight", "10/18/2026 23:00", "10/19/2026 01:00"),
+        ("Bob", "Lunch", "10/30/2026 12:00", "10/30/2026 13:00"),
+    ])
+}
+
+#[test]
+fn owner_statistics() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|_, _| true);
+    let report = report(
+        &hits,
+        NaiveDate::from_ymd(2026, 10, 19),
+        NaiveDate::from_ymd(2026, 10, 25),
+        WorkingHours::default(),
+    );
+
+    // Bob has nothing that week
+    assert_eq!(report.owners.len(), 1);
+
+    let alice = &report.owners[0];
+    assert_eq!(alice.appointments, 5);
+    assert_eq!(alice.total_minutes, 60 + 120 + 30 + 270 + 180);
+    assert_eq!(alice.average_minutes, 132);
+    assert_eq!(alice.weekdays[0].appointments, 3);
+    assert_eq!(alice.weekdays[5].appointments, 1);
+    assert_eq!(alice.hours[9].appointments, 2);
+
+    let gap = alice.longest_gap.as_ref().unwrap();
+    assert_eq!(
+        gap.start,
+        NaiveDate::from_ymd(2026, 10, 21).and_hms(17, 30, 0)
+    );
+    assert_eq!(gap.end, NaiveDate::from_ymd(2026, 10, 24).and_hms(9, 0, 0));
+
+    // The double booking counts once, and the workshop only until 17:00
+    assert_eq!(alice.working_minutes, 5 * 8 * 60);
+    assert_eq!(alice.booked_working_minutes, 120 + 240);
+    assert_eq!(alice.booked_working_percent, 15.0);
+}
+
+#[test]
+fn text_report() {
+    let apptbook = sample_book();
+    let hits = apptbook.select(|owner, _| owner.handle == "bob");
+    let text = text(&report(
+        &hits,
+        NaiveDate::from_ymd(2026, 10, 1),
+        NaiveDate::from_ymd(2026, 10, 31),
+        WorkingHours::parse("08:30-12:30").unwrap(),
+    ));
+
+    assert_eq!(
+        text,
+        "Report for 10/01/2026 to 10/31/2026\n\
+         \n\
+         Bob (bob)\n\
+         \x20 Appointments:          1\n\
+         \x20 Total duration:        1 hour (60 minutes)\n\
+         \x20 Average duration:      60 minutes\n\
+         \x20 Busiest weekdays:      Fri (1)\n\
+         \x20 Busiest hours:         12:00 (1)\n\
+         \x20 Longest gap:           none\n\
+         \x20 Working hours booked:  0.6% of 88 hours (Mon-Fri 08:30-12:30)\n"
+    );
+
+    assert!(WorkingHours::parse("17:00-09:00").is_err());
+    assert!(WorkingHours::parse("9-5").is_err());
+}
@@ -0,0 +1,149 @@
+use crate::appointment::Appointment;
+use crate::book::{Book, Hit};
+use chrono::prelude::*;
+use regex::{Regex, RegexBuilder};
+
+pub enum Pattern {
+    // Case-insensitive substring, stored lowercased
+    Text(String),
+    Regex(Regex),
+}
+
+impl Pattern {
+    pub fn text(query: &str) -> Pattern {
+        Pattern::Text(query.to_lowercase())
+    }
+
+    // Compile a case-insensitive regex
+    pub fn regex(query: &str) -> Result<Pattern, String> {
+        RegexBuilder::new(query)
+            .case_insensitive(true)
+            .build()
+            .map(Pattern::Regex)
+            .map_err(|err| format!("Invalid regex: {}", err))
+    }
+
+    pub fn is_match(&self, description: &str) -> bool {
+        match self {
+            Pattern::Text(text) => description.to_lowercase().contains(text.as_str()),
+            Pattern::Regex(re) => re.is_match(description),
+        }
+    }
+}
+
+pub struct Query {
+    pub pattern: Pattern,
+    // Only match appointments overlapping these days (inclusive)
+    pub from: Option<NaiveDate>,
+    pub to: Option<NaiveDate>,
+    // Only match appointments with every one of these tags
+    pub tags: Vec<String>,
+}
+
+impl Query {
+    pub fn is_match(&self, appt: &Appointment) -> bool {
+        if let Some(from) = self.from {
+            if appt.last_day() < from {
+                return false;
+            }
+        }
+
+        if let Some(to) = self.to {
+            if appt.start_date_time >= to.succ().and_hms(0, 0, 0) {
+                return false;
+            }
+        }
+
+        if !self.tags.iter().all(|tag| appt.tags.contains(tag)) {
+            return false;
+        }
+
+        self.pattern.is_match(&appt.description)
+    }
+}
+
+// Find matching appointments across all owners, ordered by owner then start time
+pub fn search<'a>(apptbook: &'a Book, query: &Query) -> Vec<Hit<'a>> {
+    apptbook.select(|_, appt| query.is_match(appt))
+}
+
+pub fn print_hits(hits: &[Hit]) {
+    if hits.is_empty() {
+        println!("No appointments matched the search");
+        return;
+    }
+
+    for hit in hits {
+        println!("\n> {} ({})", hit.appt.description, hit.owner.name);
+        println!("| {}", hit.appt.formatted_span());
+
+        if !hit.appt.status.is_confirmed() {
+            println!("| Status: {}", hit.appt.status.name());
+        }
+
+        if !hit.appt.tags.is_empty() {
+            println!("| Tags: {}", hit.appt.tags.join(", "));
+        }
+    }
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn search_substring_ignores_case() {
+    let apptbook = Book::sample(&[
+        ("Alice", "Dentist", "03/11/2020 07:30", "03/11/2020 08:30"),
+        (
+            "Bob",
+            "See the dentist",
+            "03/12/2020 07:30",
+            "03/12/2020 08:30",
+        ),
+        ("Bob", "Lunch", "03/12/2020 12:00", "03/12/2020 13:00"),
+    ]);
+
+    let query = Query {
+        pattern: Pattern::text("DENTIST"),
+        from: None,
+        to: None,
+        tags: Vec::new(),
+    };
+    let hits = search(&apptbook, &query);
+
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[0].owner.name, "Alice");
+    assert_eq!(hits[1].appt.description, "See the dentist");
+}
+
+#[test]
+fn search_regex_within_range() {
+    let mut apptbook = Book::sample(&[
+        ("Alice", "Dentist", "03/11/2020 07:30", "03/11/2020 08:30"),
+        ("Alice", "Dentist", "04/11/2020 07:30", "04/11/2020 08:30"),
+        ("Alice", "Doctor", "03/12/2020 07:30", "03/12/2020 08:30"),
+    ]);
+
+    let query = Query {
+        pattern: Pattern::regex("^d(entist|octor)$").unwrap(),
+        from: Some(NaiveDate::from_ymd(2020, 3, 1)),
+        to: Some(NaiveDate::from_ymd(2020, 3, 12)),
+        tags: Vec::new(),
+    };
+    let hits = search(&apptbook, &query);
+
+    assert_eq!(hits.len(), 2);
+    assert_eq!(hits[1].appt.description, "Doctor");
+
+    for appt in &mut apptbook.owners[0].appointments {
+        if appt.description == "Doctor" {
+            appt.tags = vec!["checkup".to_string()];
+        }
+    }
+    let query = Query {
+        tags: vec!["checkup".to_string()],
+        ..query
+    };
+    assert_eq!(search(&apptbook, &query)[0].appt.description, "Doctor");
+    assert!(Pattern::regex("(").is_err());
+}
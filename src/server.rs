@@ -0,0 +1,565 @@
+use crate::book::{self, Book, Hit};
+use crate::config::{self, Config, Hooks};
+use crate::filter::Filter;
+use crate::hooks::{self, Action};
+use crate::jsonl::{self, Record};
+use crate::output::{self, Format};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+// Larger request bodies are refused. A single appointment is well under this
+const MAX_BODY: usize = 1024 * 1024;
+
+// Requests are handled one at a time, so a client that stops sending or reading is dropped after
+// this long rather than holding up everyone else
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+// Handles a request against the book. Returns the response and whether the book was modified
+pub type Handler = fn(&Request, &mut Book) -> (Response, bool);
+
+pub struct Request {
+    pub method: String,
+    // Path without the query string, e.g. "/owners/alice/appointments"
+    pub path: String,
+    pub query: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Request {
+    // Header names are matched ignoring case
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+pub struct Response {
+    pub status: u16,
+    pub content_type: &'static str,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn new(status: u16, content_type: &'static str, body: String) -> Response {
+        Response {
+            status,
+            content_type,
+            headers: Vec::new(),
+            body,
+        }
+    }
+
+    pub fn with_header(mut self, name: &str, value: &str) -> Response {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    fn json(status: u16, body: String) -> Response {
+        Response::new(status, "application/json", body)
+    }
+
+    fn error(status: u16, message: &str) -> Response {
+        Response::json(
+            status,
+            format!("{}\n", serde_json::json!({ "error": message })),
+        )
+    }
+}
+
+// Serve the storage file over HTTP on localhost until the process is stopped. Requests are handled
+// one at a time
+pub fn serve(port: u16, handler: Handler) -> Result<(), String> {
+    let listener = TcpListener::bind(("127.0.0.1", port))
+        .map_err(|err| format!("Failed to listen on port {}: {}", port, err))?;
+
+    println!("Serving appointments on http://127.0.0.1:{}", port);
+
+    for stream in listener.incoming() {
+        let result = stream.and_then(|stream| handle_connection(stream, handler));
+
+        if let Err(err) = result {
+            eprintln!("Connection failed: {}", err);
+        }
+    }
+
+    Ok(())
+}
+
+fn handle_connection(mut stream: TcpStream, handler: Handler) -> io::Result<()> {
+    stream.set_read_timeout(Some(TIMEOUT))?;
+    stream.set_write_timeout(Some(TIMEOUT))?;
+
+    let response = match read_request(&mut stream)? {
+        Ok(request) => {
+            // Read the book fresh for every request so changes made with other apptbook
+            // commands while the server runs aren't lost
+            let mut apptbook = Book::load(book::STORAGE_FILE);
+            let (response, modified) = handler(&request, &mut apptbook);
+
+            if modified {
+                apptbook.save(book::STORAGE_FILE);
+            }
+
+            println!("{} {} {}", request.method, request.path, response.status);
+            response
+        }
+        Err(response) => response,
+    };
+
+    write_response(&mut stream, &response)
+}
+
+// Route a request to the book. Returns the response and whether the book was modified
+pub fn handle(request: &Request, apptbook: &mut Book) -> (Response, bool) {
+    let segments: Vec<String> = request
+        .path
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(decode)
+        .collect();
+    let segments: Vec<&str> = segments.iter().map(String::as_str).collect();
+
+    let response = match (request.method.as_str(), segments.as_slice()) {
+        ("GET", ["owners"]) => Response::json(200, output::owners(&apptbook.owners, Format::Json)),
+        ("GET", ["owners", owner, "appointments"]) => {
+            let handle = match apptbook.find(owner) {
+                Some(owner) => owner.handle.clone(),
+                None => return (Response::error(404, "No such owner"), false),
+            };
+
+            list(request, apptbook, Some(&handle))
+        }
+        ("GET", ["appointments"]) => list(request, apptbook, None),
+        ("GET", ["appointments", id]) => match parse_id(id).and_then(|id| apptbook.find_id(id)) {
+            Some(hit) => record(200, &hit),
+            None => Response::error(404, "No such appointment"),
+        },
+        ("POST", ["appointments"]) => match jsonl::parse_record(&request.body) {
+            Ok(mut row) => {
+                // New appointments always get the next ID
+                row.appt.id = 0;
+
+                let added = load_hooks().and_then(|hooks| {
+                    hooks::add(&hooks, apptbook, Action::Add, &row.owner, row.appt)
+                });
+
+                match added {
+                    Ok(id) => return (record(201, &apptbook.find_id(id).unwrap()), true),
+                    Err(message) => Response::error(403, &message),
+                }
+            }
+            Err(message) => Response::error(400, &message),
+        },
+        ("PUT", ["appointments", id]) => {
+            let id = match parse_id(id) {
+                Some(id) => id,
+                None => return (Response::error(404, "No such appointment"), false),
+            };
+
+            // Validate before removing, so a bad update leaves the appointment alone
+            let mut row = match jsonl::parse_record(&request.body) {
+                Ok(row) => row,
+                Err(message) => return (Response::error(400, &message), false),
+            };
+
+            let existing = match apptbook.find_id(id) {
+                Some(hit) => hit.appt.clone(),
+                None => return (Response::error(404, "No such appointment"), false),
+            };
+
+            // Fields the record doesn't carry are kept
+            row.appt.uid = existing.uid;
+
+            let replaced = load_hooks().and_then(|hooks| {
+                hooks::replace(&hooks, apptbook, Action::Edit, id, &row.owner, row.appt)
+            });
+
+            match replaced {
+                Ok(_) => return (record(200, &apptbook.find_id(id).unwrap()), true),
+                Err(message) => Response::error(403, &message),
+            }
+        }
+        ("DELETE", ["appointments", id]) => {
+            let id = match parse_id(id).filter(|&id| apptbook.find_id(id).is_some()) {
+                Some(id) => id,
+                None => return (Response::error(404, "No such appointment"), false),
+            };
+
+            match load_hooks().and_then(|hooks| hooks::delete(&hooks, apptbook, id)) {
+                Ok(()) => return (Response::json(204, String::new()), true),
+                Err(message) => Response::error(403, &message),
+            }
+        }
+        (_, ["owners"])
+        | (_, ["owners", _, "appointments"])
+        | (_, ["appointments"])
+        | (_, ["appointments", _]) => Response::error(405, "Method not allowed"),
+        _ => Response::error(404, "Not found"),
+    };
+
+    (response, false)
+}
+
+// List the appointments matching the request's filter parameter, or only those the owner with the
+// handle takes part in
+fn list(request: &Request, apptbook: &Book, handle: Option<&str>) -> Response {
+    let filter = query_param(&request.query, "filter").unwrap_or_default();
+
+    let filter = match Filter::parse(&filter) {
+        Ok(filter) => filter,
+        Err(message) => return Response::error(400, &message),
+    };
+
+    let hits: Vec<Hit> = match handle {
+        Some(handle) => filter
+            .select_participating(apptbook)
+            .into_iter()
+            .filter(|hit| hit.owner.handle == handle)
+            .collect(),
+        None => filter.select(apptbook),
+    };
+
+    Response::json(200, output::appointments(&hits, Format::Json))
+}
+
+fn record(status: u16, hit: &Hit) -> Response {
+    Response::json(
+        status,
+        format!(
+            "{}\n",
+            serde_json::to_string_pretty(&Record::from_hit(hit)).unwrap()
+        ),
+    )
+}
+
+// Hooks are read for every change, so editing them doesn't need a restart. While the config file
+// doesn't parse, changes are refused rather than made without their hooks
+pub fn load_hooks() -> Result<Hooks, String> {
+    Config::load(config::CONFIG_FILE).map(|config| config.hooks)
+}
+
+fn parse_id(id: &str) -> Option<u64> {
+    id.parse().ok()
+}
+
+// Find a parameter in a query string like "filter=owner%3Aalice&x=1", decoded
+fn query_param(query: &str, name: &str) -> Option<String> {
+    query.split('&').find_map(|pair| {
+        let mut parts = pair.splitn(2, '=');
+
+        if parts.next() == Some(name) {
+            Some(decode(&parts.next().unwrap_or("").replace('+', " ")))
+        } else {
+            None
+        }
+    })
+}
+
+// Decode %XX escapes in a URL. Malformed escapes are kept as they are
+pub fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let escape = bytes
+            .get(i + 1..i + 3)
+            // from_str_radix accepts a sign, so check the digits first
+            .filter(|hex| hex.iter().all(u8::is_ascii_hexdigit))
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match (bytes[i], escape) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+// Read one request from the connection. A malformed request gives the error response to send
+fn read_request(stream: &mut TcpStream) -> io::Result<Result<Request, Response>> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+    reader.read_line(&mut line)?;
+
+    let mut parts = line.split_whitespace();
+    let (method, target) = match (parts.next(), parts.next()) {
+        (Some(method), Some(target)) => (method.to_string(), target.to_string()),
+        _ => return Ok(Err(Response::error(400, "Malformed request"))),
+    };
+
+    let mut headers = Vec::new();
+
+    loop {
+        let mut header = String::new();
+
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+
+        if let Some(index) = header.find(':') {
+            headers.push((
+                header[..index].trim().to_string(),
+                header[index + 1..].trim().to_string(),
+            ));
+        }
+    }
+
+    let content_length = match headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .map(|(_, value)| value.parse())
+    {
+        Some(Ok(length)) => length,
+        Some(Err(_)) => return Ok(Err(Response::error(400, "Invalid Content-Length"))),
+        None => 0,
+    };
+
+    if content_length > MAX_BODY {
+        return Ok(Err(Response::error(413, "Request body too large")));
+    }
+
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body)?;
+
+    let (path, query) = match target.find('?') {
+        Some(index) => (target[..index].to_string(), target[index + 1..].to_string()),
+        None => (target, String::new()),
+    };
+
+    Ok(Ok(Request {
+        method,
+        path,
+        query,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    }))
+}
+
+fn write_response(stream: &mut TcpStream, response: &Response) -> io::Result<()> {
+    let reason = match response.status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        207 => "Multi-Status",
+        301 => "Moved Permanently",
+        400 => "Bad Request",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        412 => "Precondition Failed",
+        413 => "Payload Too Large",
+        _ => "Error",
+    };
+
+    write!(stream, "HTTP/1.1 {} {}\r\n", response.status, reason)?;
+
+    if !response.body.is_empty() {
+        write!(stream, "Content-Type: {}\r\n", response.content_type)?;
+    }
+
+    for (name, value) in &response.headers {
+        write!(stream, "{}: {}\r\n", name, value)?;
+    }
+
+    write!(
+        stream,
+        "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        response.body.len(),
+        response.body
+    )?;
+
+    stream.flush()
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn request(method: &str, target: &str, body: &str) -> Request {
+    let (path, query) = match target.find('?') {
+        Some(index) => (&target[..index], &target[index + 1..]),
+        None => (target, ""),
+    };
+
+    Request {
+        method: method.to_string(),
+        path: path.to_string(),
+        query: query.to_string(),
+        headers: Vec::new(),
+        body: body.to_string(),
+    }
+}
+
+#[cfg(test)]
+fn json(response: &Response) -> serde_json::Value {
+    serde_json::from_str(&response.body).unwrap()
+}
+
+#[test]
+fn create_update_delete_appointments() {
+    let mut apptbook = Book::default();
+
+    let (response, modified) = handle(
+        &request(
+            "POST",
+            "/appointments",
+            r#"{"owner": "Alice", "description": "Review", "start": "2026-11-02T10:00:00", "duration_minutes": 60}"#,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 201);
+    assert!(modified);
+    assert_eq!(json(&response)["id"], 1);
+    assert_eq!(json(&response)["end"], "2026-11-02T11:00:00");
+
+    let (response, _) = handle(
+        &request(
+            "PUT",
+            "/appointments/1",
+            r#"{"owner": "Bob", "description": "Review", "start": "2026-11-02T10:00:00", "end": "2026-11-02T10:30:00"}"#,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 200);
+    assert_eq!(json(&response)["owner_handle"], "bob");
+    assert_eq!(apptbook.find_id(1).unwrap().appt.duration_minutes(), 30);
+
+    let (response, modified) = handle(&request("DELETE", "/appointments/1", ""), &mut apptbook);
+    assert_eq!(response.status, 204);
+    assert!(modified);
+    assert!(apptbook.find_id(1).is_none());
+
+    let (response, modified) = handle(&request("DELETE", "/appointments/1", ""), &mut apptbook);
+    assert_eq!(response.status, 404);
+    assert!(!modified);
+}
+
+#[test]
+fn invalid_appointments_are_rejected() {
+    let mut apptbook = Book::default();
+
+    let (response, modified) = handle(
+        &request(
+            "POST",
+            "/appointments",
+            r#"{"owner": "Alice", "description": "Backwards", "start": "2026-11-02T10:00:00", "end": "2026-11-02T09:00:00"}"#,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 400);
+    assert!(!modified);
+    assert_eq!(
+        json(&response)["error"],
+        "Invalid time. End time cannot occur before start time"
+    );
+
+    let (response, modified) = handle(
+        &request(
+            "POST",
+            "/appointments",
+            r#"{"owner": "Alice", "description": "Forever", "start": "2026-11-02T10:00:00", "duration_minutes": 99999999999999}"#,
+        ),
+        &mut apptbook,
+    );
+    assert_eq!(response.status, 400);
+    assert!(!modified);
+    assert_eq!(
+        json(&response)["error"],
+        "Invalid duration_minutes: 99999999999999"
+    );
+
+    assert_eq!(
+        handle(&request("POST", "/appointments", "{"), &mut apptbook)
+            .0
+            .status,
+        400
+    );
+    assert_eq!(
+        handle(&request("PATCH", "/appointments", ""), &mut apptbook)
+            .0
+            .status,
+        405
+    );
+    assert_eq!(
+        handle(&request("GET", "/nowhere", ""), &mut apptbook)
+            .0
+            .status,
+        404
+    );
+    assert!(apptbook.is_empty());
+}
+
+#[test]
+fn list_owner_appointments_with_filter() {
+    let mut apptbook = Book::default();
+
+    for (owner, description) in &[
+        ("Alice Smith", "Review"),
+        ("Alice Smith", "Lunch"),
+        ("Bob", "Review"),
+    ] {
+        let body = format!(
+            r#"{{"owner": "{}", "description": "{}", "start": "2026-11-02T10:00:00", "duration_minutes": 30}}"#,
+            owner, description
+        );
+        handle(&request("POST", "/appointments", &body), &mut apptbook);
+    }
+
+    let (response, _) = handle(&request("GET", "/owners", ""), &mut apptbook);
+    assert_eq!(json(&response)[0]["handle"], "alice-smith");
+
+    let (response, _) = handle(
+        &request(
+            "GET",
+            "/owners/Alice%20Smith/appointments?filter=text%3Areview",
+            "",
+        ),
+        &mut apptbook,
+    );
+    let hits = json(&response);
+    assert_eq!(hits.as_array().unwrap().len(), 1);
+    assert_eq!(hits[0]["id"], 1);
+
+    let (response, _) = handle(
+        &request("GET", "/appointments?filter=text:review+owner:bob", ""),
+        &mut apptbook,
+    );
+    assert_eq!(json(&response)[0]["id"], 3);
+
+    assert_eq!(
+        handle(
+            &request("GET", "/owners/carol/appointments", ""),
+            &mut apptbook
+        )
+        .0
+        .status,
+        404
+    );
+}
+
+#[test]
+fn decode_url_escapes() {
+    assert_eq!(decode("Alice%20Smith"), "Alice Smith");
+    assert_eq!(decode("caf%C3%A9"), "café");
+    assert_eq!(decode("100%"), "100%");
+    assert_eq!(decode("%+1"), "%+1");
+    assert_eq!(
+        query_param("x=1&filter=owner%3Aalice+text%3Areview", "filter"),
+        Some("owner:alice text:review".to_string())
+    );
+    assert_eq!(query_param("x=1", "filter"), None);
+}
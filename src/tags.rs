@@ -0,0 +1,144 @@
+use crate::book::Book;
+use crate::config::Hooks;
+use crate::hooks::{self, Action};
+
+// Tags are kept lowercase with dashes for spaces, so "Client Meeting" and "client-meeting" are the
+// same tag
+pub fn normalize(tag: &str) -> String {
+    tag.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .to_lowercase()
+}
+
+// Parse comma-separated tags into a sorted list without duplicates. "none" or an empty string
+// means no tags
+pub fn parse(input: &str) -> Vec<String> {
+    if input.trim().eq_ignore_ascii_case("none") {
+        return Vec::new();
+    }
+
+    let mut tags: Vec<String> = input
+        .split(',')
+        .map(normalize)
+        .filter(|tag| !tag.is_empty())
+        .collect();
+
+    tags.sort();
+    tags.dedup();
+    tags
+}
+
+// Every tag in the book with how many appointments have it, most used first
+pub fn counts(apptbook: &Book) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+
+    for owner in &apptbook.owners {
+        for tag in owner.appointments.iter().flat_map(|appt| &appt.tags) {
+            match counts.iter_mut().find(|(other, _)| other == tag) {
+                Some((_, count)) => *count += 1,
+                None => counts.push((tag.clone(), 1)),
+            }
+        }
+    }
+
+    counts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)));
+    counts
+}
+
+// Replace the tags in from with into on every appointment, merging them if into is already
+// used. Each changed appointment is an edit the hooks can refuse. Returns how many appointments
+// changed and the messages of any refusals
+pub fn rename(
+    hooks: &Hooks,
+    apptbook: &mut Book,
+    from: &[String],
+    into: &str,
+) -> (usize, Vec<String>) {
+    let from: Vec<String> = from.iter().map(|tag| normalize(tag)).collect();
+    let into = normalize(into);
+
+    let changes: Vec<(u64, String, _)> = apptbook
+        .select(|_, appt| appt.tags.iter().any(|tag| from.contains(tag)))
+        .into_iter()
+        .map(|hit| {
+            let mut appt = hit.appt.clone();
+            appt.tags.retain(|tag| !from.contains(tag));
+            appt.tags.push(into.clone());
+            appt.tags.sort();
+            appt.tags.dedup();
+
+            (hit.appt.id, hit.owner.handle.clone(), appt)
+        })
+        .collect();
+
+    let mut changed = 0;
+    let mut refusals = Vec::new();
+
+    for (id, handle, appt) in changes {
+        match hooks::replace(hooks, apptbook, Action::Edit, id, &handle, appt) {
+            Ok(_) => changed += 1,
+            Err(message) => refusals.push(message),
+        }
+    }
+
+    (changed, refusals)
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn parse_normalizes_tags() {
+    assert_eq!(
+        parse(" Client Meeting, internal,,client-meeting "),
+        vec!["client-meeting", "internal"]
+    );
+    assert!(parse("none").is_empty());
+    assert!(parse("").is_empty());
+}
+
+#[test]
+fn rename_merges_tags() {
+    use crate::appointment::Appointment;
+
+    let mut apptbook = Book::default();
+    let appt = |description: &str, tags: &[&str]| Appointment {
+        description: description.to_string(),
+        tags: tags.iter().map(|tag| tag.to_string()).collect(),
+        ..Default::default()
+    };
+
+    apptbook
+        .add("Alice", appt("Review", &["client", "urgent"]))
+        .unwrap();
+    apptbook.add("Alice", appt("Sync", &["customer"])).unwrap();
+    apptbook.add("Bob", appt("Lunch", &[])).unwrap();
+
+    assert_eq!(
+        counts(&apptbook),
+        vec![
+            ("client".to_string(), 1),
+            ("customer".to_string(), 1),
+            ("urgent".to_string(), 1)
+        ]
+    );
+
+    let (changed, refusals) = rename(
+        &Hooks::default(),
+        &mut apptbook,
+        &["Customer".to_string(), "client".to_string()],
+        "Clients",
+    );
+
+    assert_eq!(changed, 2);
+    assert!(refusals.is_empty());
+    assert_eq!(
+        counts(&apptbook),
+        vec![("clients".to_string(), 2), ("urgent".to_string(), 1)]
+    );
+    assert_eq!(
+        apptbook.find_id(1).unwrap().appt.tags,
+        vec!["clients", "urgent"]
+    );
+}
@@ -0,0 +1,172 @@
+use crate::appointment::Appointment;
+use crate::book::Book;
+use crate::remind;
+use crate::tags;
+use chrono::{Duration, NaiveDateTime};
+use serde::{Deserialize, Serialize};
+
+// A named kind of appointment that's entered often, such as "Follow-up". Adding an appointment
+// from a template only takes an owner and a start
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Template {
+    // Unique within the book, ignoring case
+    pub name: String,
+    pub description: String,
+    pub duration_minutes: i64,
+    // Normalized by tags::normalize and kept sorted
+    #[serde(default)]
+    pub tags: Vec<String>,
+    // Minutes before the start, smallest first
+    #[serde(default)]
+    pub reminders: Vec<i64>,
+}
+
+impl Template {
+    // Build a template from the values given to the template add command, validating them
+    pub fn new(
+        name: &str,
+        description: &str,
+        duration: &str,
+        tag_list: &str,
+        reminders: &str,
+    ) -> Result<Template, String> {
+        if name.trim().is_empty() {
+            return Err("Template name cannot be empty".to_string());
+        }
+
+        if description.trim().is_empty() {
+            return Err("Description cannot be empty".to_string());
+        }
+
+        let duration_minutes = match duration.trim().parse::<i64>() {
+            Ok(minutes) if minutes > 0 && minutes <= remind::MAX_OFFSET_MINUTES => minutes,
+            _ => {
+                return Err(format!(
+                    "Invalid duration. Must be a number of minutes: {}",
+                    duration.trim()
+                ))
+            }
+        };
+
+        Ok(Template {
+            name: name.split_whitespace().collect::<Vec<_>>().join(" "),
+            description: description.trim().to_string(),
+            duration_minutes,
+            tags: tags::parse(tag_list),
+            reminders: remind::parse_offsets(reminders)?,
+        })
+    }
+
+    // An appointment of this kind starting at start. Fails if it would end past the last date
+    // chrono can represent
+    pub fn appointment(&self, start: NaiveDateTime) -> Result<Appointment, String> {
+        let end = start
+            .checked_add_signed(Duration::minutes(self.duration_minutes))
+            .ok_or_else(|| "Invalid duration. The appointment would end too late".to_string())?;
+
+        Ok(Appointment {
+            description: self.description.clone(),
+            start_date_time: start,
+            end_date_time: end,
+            tags: self.tags.clone(),
+            reminders: self.reminders.clone(),
+            ..Default::default()
+        })
+    }
+
+    // One line describing the template, e.g. "Follow-up: Follow-up visit (20 minutes)"
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "{}: {} ({} minutes)",
+            self.name, self.description, self.duration_minutes
+        );
+
+        if !self.tags.is_empty() {
+            summary.push_str(&format!(" | Tags: {}", self.tags.join(", ")));
+        }
+
+        if !self.reminders.is_empty() {
+            summary.push_str(&format!(
+                " | Reminders: {}",
+                remind::format_offsets(&self.reminders)
+            ));
+        }
+
+        summary
+    }
+}
+
+// Find a template by name, ignoring case
+pub fn find<'a>(apptbook: &'a Book, name: &str) -> Option<&'a Template> {
+    apptbook
+        .templates
+        .iter()
+        .find(|template| template.name.eq_ignore_ascii_case(name.trim()))
+}
+
+// Add a template to the book, refusing a name that's already taken
+pub fn add(apptbook: &mut Book, template: Template) -> Result<(), String> {
+    if find(apptbook, &template.name).is_some() {
+        return Err(format!("A template named {} already exists", template.name));
+    }
+
+    apptbook.templates.push(template);
+    apptbook
+        .templates
+        .sort_by_key(|template| template.name.to_lowercase());
+
+    Ok(())
+}
+
+// Remove the template with the name, returning whether there was one
+pub fn delete(apptbook: &mut Book, name: &str) -> bool {
+    let count = apptbook.templates.len();
+
+    apptbook
+        .templates
+        .retain(|template| !template.name.eq_ignore_ascii_case(name.trim()));
+
+    apptbook.templates.len() < count
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[test]
+fn templates_make_appointments() {
+    use chrono::NaiveDate;
+
+    let mut apptbook = Book::default();
+    let template =
+        Template::new(" Follow-up ", "Follow-up visit", "20", "Clinic", "1h, 15m").unwrap();
+    assert_eq!(template.name, "Follow-up");
+    assert_eq!(
+        template.summary(),
+        "Follow-up: Follow-up visit (20 minutes) | Tags: clinic | Reminders: 15m, 1h"
+    );
+
+    add(&mut apptbook, template).unwrap();
+    assert!(add(
+        &mut apptbook,
+        Template::new("FOLLOW-UP", "Again", "30", "", "").unwrap()
+    )
+    .is_err());
+
+    let start = NaiveDate::from_ymd(2026, 10, 20).and_hms(9, 40, 0);
+    let appt = find(&apptbook, "follow-up")
+        .unwrap()
+        .appointment(start)
+        .unwrap();
+    assert_eq!(appt.description, "Follow-up visit");
+    assert_eq!(appt.end_date_time, start + Duration::minutes(20));
+    assert_eq!(appt.tags, vec!["clinic"]);
+    assert_eq!(appt.reminders, vec![15, 60]);
+
+    assert!(Template::new("Intake", "New patient intake", "0", "", "").is_err());
+    assert!(Template::new("Big", "Big one", "99999999999999", "", "").is_err());
+    assert!(Template::new("Intake", "New patient intake", "60", "", "soon").is_err());
+    assert!(Template::new(" ", "New patient intake", "60", "", "").is_err());
+
+    assert!(delete(&mut apptbook, "Follow-Up"));
+    assert!(!delete(&mut apptbook, "Follow-up"));
+}
@@ -0,0 +1,1039 @@
+use crate::appointment::{self, Appointment, DATE_TIME_FORMAT};
+use crate::book::Book;
+use crate::config::Hooks;
+use crate::grid::fit;
+use crate::hooks::{self, Action};
+use chrono::prelude::*;
+use chrono::Duration;
+use std::io::{self, Read, Write};
+use std::process::{Command, Stdio};
+
+// Width of the owner list pane, not counting the separator
+const OWNERS_WIDTH: usize = 22;
+
+// ANSI escape sequences
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const REVERSE: &str = "\x1b[7m";
+const RED: &str = "\x1b[31m";
+
+const FIELD_NAMES: [&str; 4] = ["Owner", "Description", "Start", "End"];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Key {
+    Char(char),
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Tab,
+    BackTab,
+    Backspace,
+    Delete,
+    Esc,
+    Interrupt,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum View {
+    Day,
+    Week,
+    Month,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Focus {
+    Owners,
+    Appointments,
+}
+
+// The add/edit form. Fields are owner, description, start and end, with the dates entered like
+// at the add prompts
+struct Form {
+    // ID of the appointment being edited, or None when adding one
+    editing: Option<u64>,
+    fields: [String; 4],
+    field: usize,
+    error: Option<String>,
+}
+
+enum Mode {
+    Browse,
+    Form(Form),
+    ConfirmDelete(u64),
+}
+
+struct App<'a> {
+    apptbook: &'a mut Book,
+    hooks: Hooks,
+    owner: usize,
+    focus: Focus,
+    view: View,
+    // The day the view is on. Week and month views show the week or month containing it
+    date: NaiveDate,
+    // Index of the selected appointment among those in view
+    selected: usize,
+    mode: Mode,
+    message: Option<String>,
+    modified: bool,
+    quit: bool,
+}
+
+// Run the full-screen interface until the user quits. Returns whether the book was modified
+pub fn run(apptbook: &mut Book, hooks: &Hooks) -> Result<bool, String> {
+    let _terminal = Terminal::enter()?;
+    let mut app = App::new(apptbook, hooks.clone(), Local::now().naive_local().date());
+    let mut stdin = io::stdin();
+    let mut buffer = [0; 64];
+
+    while !app.quit {
+        let (rows, cols) = terminal_size();
+        draw(&app.render(rows, cols));
+
+        // A key's whole escape sequence arrives in one read
+        let count = stdin
+            .read(&mut buffer)
+            .map_err(|err| format!("Failed to read input: {}", err))?;
+
+        if count == 0 {
+            break;
+        }
+
+        for key in parse_keys(&buffer[..count]) {
+            app.handle_key(key);
+        }
+    }
+
+    Ok(app.modified)
+}
+
+impl<'a> App<'a> {
+    fn new(apptbook: &'a mut Book, hooks: Hooks, today: NaiveDate) -> App<'a> {
+        App {
+            apptbook,
+            hooks,
+            owner: 0,
+            focus: Focus::Appointments,
+            view: View::Week,
+            date: today,
+            selected: 0,
+            mode: Mode::Browse,
+            message: None,
+            modified: false,
+            quit: false,
+        }
+    }
+
+    // First day of the view and the day after its last
+    fn period(&self) -> (NaiveDate, NaiveDate) {
+        match self.view {
+            View::Day => (self.date, self.date.succ()),
+            View::Week => {
+                let monday =
+                    self.date - Duration::days(self.date.weekday().num_days_from_monday() as i64);
+                (monday, monday + Duration::days(7))
+            }
+            View::Month => {
+                let first = self.date.with_day(1).unwrap();
+                (first, add_months(first, 1))
+            }
+        }
+    }
+
+    // The selected owner's appointments occupying any day in view, including those they're
+    // attending
+    fn visible(&self) -> Vec<&Appointment> {
+        let (start, end) = self.period();
+
+        match self.apptbook.owners.get(self.owner) {
+            Some(owner) => self
+                .apptbook
+                .attending(&owner.handle)
+                .into_iter()
+                .filter(|appt| appt.start_date_time.date() < end && appt.last_day() >= start)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn handle_key(&mut self, key: Key) {
+        if key == Key::Interrupt {
+            self.quit = true;
+            return;
+        }
+
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Browse => self.browse_key(key),
+            Mode::Form(form) => self.form_key(form, key),
+            Mode::ConfirmDelete(id) => {
+                if key == Key::Char('y') {
+                    let result = match self.attendee(id) {
+                        Some(handle) => hooks::decline(&self.hooks, self.apptbook, id, &handle)
+                            .map(|()| "Appointment declined"),
+                        None => hooks::delete(&self.hooks, self.apptbook, id)
+                            .map(|()| "Appointment deleted"),
+                    };
+
+                    match result {
+                        Ok(message) => {
+                            self.modified = true;
+                            self.selected = self.selected.saturating_sub(1);
+                            self.message = Some(message.to_string());
+                        }
+                        Err(message) => self.message = Some(message),
+                    }
+                } else {
+                    self.message = Some("Canceled deleting appointment".to_string());
+                }
+            }
+        }
+    }
+
+    fn browse_key(&mut self, key: Key) {
+        self.message = None;
+
+        match key {
+            Key::Char('q') => self.quit = true,
+            Key::Tab | Key::BackTab => {
+                self.focus = match self.focus {
+                    Focus::Owners => Focus::Appointments,
+                    Focus::Appointments => Focus::Owners,
+                }
+            }
+            Key::Up | Key::Char('k') => match self.focus {
+                Focus::Owners => self.select_owner(self.owner.saturating_sub(1)),
+                Focus::Appointments => self.selected = self.selected.saturating_sub(1),
+            },
+            Key::Down | Key::Char('j') => match self.focus {
+                Focus::Owners => {
+                    if self.owner + 1 < self.apptbook.owners.len() {
+                        self.select_owner(self.owner + 1);
+                    }
+                }
+                Focus::Appointments => {
+                    if self.selected + 1 < self.visible().len() {
+                        self.selected += 1;
+                    }
+                }
+            },
+            Key::Left | Key::Char('h') => self.shift(-1),
+            Key::Right | Key::Char('l') => self.shift(1),
+            Key::Char('d') => self.set_view(View::Day),
+            Key::Char('w') => self.set_view(View::Week),
+            Key::Char('m') => self.set_view(View::Month),
+            Key::Char('t') => {
+                self.date = Local::now().naive_local().date();
+                self.selected = 0;
+            }
+            Key::Char('a') => {
+                let owner = match self.apptbook.owners.get(self.owner) {
+                    Some(owner) => owner.name.clone(),
+                    None => String::new(),
+                };
+                let day = self.date.format("%m/%d/%Y");
+
+                self.mode = Mode::Form(Form {
+                    editing: None,
+                    fields: [
+                        owner,
+                        String::new(),
+                        format!("{} 09:00", day),
+                        format!("{} 10:00", day),
+                    ],
+                    field: 1,
+                    error: None,
+                });
+            }
+            Key::Char('e') | Key::Enter => {
+                if let Some(appt) = self.visible().get(self.selected) {
+                    // All-day appointments are edited as their first and last day
+                    let (start, end) = if appt.all_day {
+                        (
+                            appt.start_date_time.format("%m/%d/%Y").to_string(),
+                            appt.last_day().format("%m/%d/%Y").to_string(),
+                        )
+                    } else {
+                        (
+                            appt.start_date_time.format(DATE_TIME_FORMAT).to_string(),
+                            appt.end_date_time.format(DATE_TIME_FORMAT).to_string(),
+                        )
+                    };
+
+                    self.mode = Mode::Form(Form {
+                        editing: Some(appt.id),
+                        fields: [
+                            // The organizer, when the appointment is one the owner is attending
+                            self.apptbook.find_id(appt.id).unwrap().owner.name.clone(),
+                            appt.description.clone(),
+                            start,
+                            end,
+                        ],
+                        field: 1,
+                        error: None,
+                    });
+                }
+            }
+            Key::Char('x') | Key::Delete => {
+                let confirm = self.visible().get(self.selected).map(|appt| {
+                    let verb = match self.attendee(appt.id) {
+                        Some(_) => "Decline",
+                        None => "Delete",
+                    };
+                    let question = format!("{} \"{}\"? (y/n)", verb, appt.description);
+                    (question, appt.id)
+                });
+
+                if let Some((question, id)) = confirm {
+                    self.message = Some(question);
+                    self.mode = Mode::ConfirmDelete(id);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn form_key(&mut self, mut form: Form, key: Key) {
+        match key {
+            Key::Esc => {
+                self.message = Some("Canceled".to_string());
+                return;
+            }
+            Key::Tab | Key::Down => {
+                form.error = validate_field(&form);
+                form.field = (form.field + 1) % FIELD_NAMES.len();
+            }
+            Key::BackTab | Key::Up => {
+                form.field = (form.field + FIELD_NAMES.len() - 1) % FIELD_NAMES.len();
+            }
+            Key::Enter if form.field + 1 < FIELD_NAMES.len() => {
+                form.error = validate_field(&form);
+                form.field += 1;
+            }
+            Key::Enter => match build(&form) {
+                Ok((owner, appt)) => match self.save(form.editing, &owner, appt) {
+                    Ok(()) => return,
+                    Err(message) => form.error = Some(message),
+                },
+                Err(message) => form.error = Some(message),
+            },
+            Key::Backspace => {
+                form.fields[form.field].pop();
+                form.error = None;
+            }
+            Key::Char(c) => {
+                form.fields[form.field].push(c);
+                form.error = None;
+            }
+            _ => (),
+        }
+
+        self.mode = Mode::Form(form);
+    }
+
+    // Add the appointment, or replace the one being edited, then show it
+    fn save(
+        &mut self,
+        editing: Option<u64>,
+        owner: &str,
+        mut appt: Appointment,
+    ) -> Result<(), String> {
+        let old = editing.and_then(|id| self.apptbook.find_id(id).map(|hit| hit.appt.clone()));
+
+        let id = match old {
+            Some(old) => {
+                appt.uid = old.uid;
+                appt.reminders = old.reminders;
+                appt.tags = old.tags;
+                appt.location = old.location;
+                appt.notes = old.notes;
+                appt.attendees = old.attendees;
+                appt.status = old.status;
+                hooks::replace(
+                    &self.hooks,
+                    self.apptbook,
+                    Action::Edit,
+                    old.id,
+                    owner,
+                    appt,
+                )?
+            }
+            None => hooks::add(&self.hooks, self.apptbook, Action::Add, owner, appt)?,
+        };
+
+        self.modified = true;
+        self.message = Some("Appointment saved".to_string());
+
+        let hit = self.apptbook.find_id(id).unwrap();
+        let (handle, date) = (hit.owner.handle.clone(), hit.appt.start_date_time.date());
+
+        self.owner = self
+            .apptbook
+            .owners
+            .iter()
+            .position(|owner| owner.handle == handle)
+            .unwrap();
+        self.date = date;
+        self.selected = self
+            .visible()
+            .iter()
+            .position(|appt| appt.id == id)
+            .unwrap_or(0);
+
+        Ok(())
+    }
+
+    // The selected owner's handle when they're only attending the appointment with the ID.
+    // Deleting it from their schedule declines it for them, leaving it for everyone else
+    fn attendee(&self, id: u64) -> Option<String> {
+        let handle = &self.apptbook.owners.get(self.owner)?.handle;
+
+        match self.apptbook.find_id(id) {
+            Some(hit) if &hit.owner.handle != handle => Some(handle.clone()),
+            _ => None,
+        }
+    }
+
+    fn select_owner(&mut self, index: usize) {
+        self.owner = index;
+        self.selected = 0;
+    }
+
+    fn set_view(&mut self, view: View) {
+        self.view = view;
+        self.selected = 0;
+    }
+
+    // Move the view back or forward by its length
+    fn shift(&mut self, direction: i64) {
+        self.date = match self.view {
+            View::Day => self.date + Duration::days(direction),
+            View::Week => self.date + Duration::days(7 * direction),
+            View::Month => add_months(self.date, direction as i32),
+        };
+        self.selected = 0;
+    }
+
+    // Appointments of the form's owner that the form's times would overlap
+    fn form_conflicts(&self, form: &Form) -> Vec<&Appointment> {
+        let (owner, appt) = match build(form) {
+            Ok(built) => built,
+            Err(_) => return Vec::new(),
+        };
+
+        match self.apptbook.find(&owner) {
+            Some(owner) => self
+                .apptbook
+                .attending(&owner.handle)
+                .into_iter()
+                .filter(|other| Some(other.id) != form.editing && other.conflicts_with(&appt))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn has_conflict(&self, appt: &Appointment) -> bool {
+        self.apptbook
+            .attending(&self.apptbook.owners[self.owner].handle)
+            .into_iter()
+            .any(|other| other.id != appt.id && other.conflicts_with(appt))
+    }
+
+    // Lay out the screen as lines of text with ANSI styling
+    fn render(&self, rows: usize, cols: usize) -> Vec<String> {
+        if rows < 8 || cols < OWNERS_WIDTH + 20 {
+            return vec!["Terminal too small".to_string()];
+        }
+
+        let height = rows - 3;
+        let width = cols - OWNERS_WIDTH - 1;
+        let mut lines = Vec::new();
+
+        let owner_name = match self.apptbook.owners.get(self.owner) {
+            Some(owner) => owner.name.as_str(),
+            None => "No owners",
+        };
+        lines.push(styled(
+            &format!(" apptbook | {} | {}", owner_name, self.title()),
+            cols,
+            REVERSE,
+        ));
+
+        let pane = match &self.mode {
+            Mode::Form(form) => self.form_lines(form),
+            _ => self.view_lines(height),
+        };
+
+        for row in 0..height {
+            let owner = match self.apptbook.owners.get(row) {
+                Some(owner) if row == self.owner && self.focus == Focus::Owners => {
+                    styled(&format!(" {}", owner.name), OWNERS_WIDTH, REVERSE)
+                }
+                Some(owner) if row == self.owner => {
+                    styled(&format!(" {}", owner.name), OWNERS_WIDTH, BOLD)
+                }
+                Some(owner) => styled(&format!(" {}", owner.name), OWNERS_WIDTH, ""),
+                None => fit("", OWNERS_WIDTH),
+            };
+
+            let right = match pane.get(row) {
+                Some((text, style)) => styled(text, width, style),
+                None => String::new(),
+            };
+
+            lines.push(format!("{}|{}", owner, right));
+        }
+
+        lines.push(match &self.message {
+            Some(message) => styled(&format!(" {}", message), cols, BOLD),
+            None => String::new(),
+        });
+
+        let help = match self.mode {
+            Mode::Browse => {
+                " Tab pane  Up/Down select  Left/Right move  d/w/m view  t today  a add  e edit  x delete  q quit"
+            }
+            Mode::Form(_) => " Tab/Down next field  Up previous  Enter next/save  Esc cancel",
+            Mode::ConfirmDelete(_) => " y delete  any other key cancels",
+        };
+        lines.push(styled(help, cols, DIM));
+
+        lines
+    }
+
+    fn title(&self) -> String {
+        let (start, _) = self.period();
+
+        match self.view {
+            View::Day => self.date.format("%A, %m/%d/%Y").to_string(),
+            View::Week => format!("Week of {}", start.format("%m/%d/%Y")),
+            View::Month => start.format("%B %Y").to_string(),
+        }
+    }
+
+    // Appointments in view, grouped under a heading per day in week and month views. Scrolled so
+    // the selected appointment is visible
+    fn view_lines(&self, height: usize) -> Vec<(String, &'static str)> {
+        if self.apptbook.owners.is_empty() {
+            return vec![(
+                " No owners yet. Press a to add an appointment".to_string(),
+                DIM,
+            )];
+        }
+
+        let (period_start, _) = self.period();
+        let mut lines = Vec::new();
+        let mut selected_line = 0;
+        let mut current_day = None;
+
+        for (index, appt) in self.visible().into_iter().enumerate() {
+            let day = appt.start_date_time.date().max(period_start);
+
+            if self.view != View::Day && current_day != Some(day) {
+                lines.push((format!(" {}", day.format("%a %m/%d/%Y")), BOLD));
+                current_day = Some(day);
+            }
+
+            let end = if appt.start_date_time.date() == appt.end_date_time.date() {
+                appt.end_date_time.format("%H:%M").to_string()
+            } else {
+                appt.end_date_time.format("%m/%d %H:%M").to_string()
+            };
+            let conflict = self.has_conflict(appt);
+
+            let mut text = if appt.all_day {
+                format!(
+                    " {} All day {} ({})",
+                    if conflict { "!" } else { " " },
+                    appt.description.replace('\n', " "),
+                    appt.formatted_duration()
+                )
+            } else {
+                format!(
+                    " {} {}-{} {} ({} min)",
+                    if conflict { "!" } else { " " },
+                    appt.start_date_time.format("%H:%M"),
+                    end,
+                    appt.description.replace('\n', " "),
+                    appt.duration_minutes()
+                )
+            };
+
+            if !appt.status.is_confirmed() {
+                text.push_str(&format!(" [{}]", appt.status.name()));
+            }
+
+            // Cancelled appointments are dimmed, and never conflict
+            let selected = index == self.selected && self.focus == Focus::Appointments;
+            let style = match (selected, conflict) {
+                (true, true) => "\x1b[7;31m",
+                (true, false) => REVERSE,
+                (false, true) => RED,
+                (false, false) if appt.is_cancelled() => DIM,
+                (false, false) => "",
+            };
+
+            if index == self.selected {
+                selected_line = lines.len();
+            }
+
+            lines.push((text, style));
+        }
+
+        if lines.is_empty() {
+            lines.push((" No appointments".to_string(), DIM));
+        }
+
+        let offset = (selected_line + 1).saturating_sub(height);
+        lines.split_off(offset)
+    }
+
+    fn form_lines(&self, form: &Form) -> Vec<(String, &'static str)> {
+        let title = match form.editing {
+            Some(_) => " Edit appointment",
+            None => " Add appointment",
+        };
+        let mut lines = vec![(title.to_string(), BOLD), (String::new(), "")];
+
+        for (index, name) in FIELD_NAMES.iter().enumerate() {
+            let value = &form.fields[index];
+            let hint = if index >= 2 {
+                "  (mm/dd/yyyy hh:mm, or mm/dd/yyyy for all day)"
+            } else {
+                ""
+            };
+
+            if index == form.field {
+                lines.push((format!(" {:12} {}_{}", name, value, hint), REVERSE));
+            } else {
+                lines.push((format!(" {:12} {}", name, value), ""));
+            }
+        }
+
+        lines.push((String::new(), ""));
+
+        if let Some(error) = &form.error {
+            lines.push((format!(" {}", error), RED));
+        }
+
+        for other in self.form_conflicts(form) {
+            let (start, end) = other.formatted_times();
+            lines.push((
+                format!(
+                    " ! Conflicts with {} ({} to {})",
+                    other.description, start, end
+                ),
+                RED,
+            ));
+        }
+
+        lines
+    }
+}
+
+// Check the field being left, giving the same messages as the add prompts
+fn validate_field(form: &Form) -> Option<String> {
+    let value = &form.fields[form.field];
+
+    match form.field {
+        0 if value.trim().is_empty() => Some("Owner cannot be empty".to_string()),
+        2 | 3 if appointment::parse_date(value).is_ok() => None,
+        2 | 3 => appointment::parse_date_time(value, DATE_TIME_FORMAT).err(),
+        _ => None,
+    }
+}
+
+// The form's owner and appointment, if every field is valid. Dates without times make an all-day
+// appointment
+fn build(form: &Form) -> Result<(String, Appointment), String> {
+    let [owner, description, start, end] = &form.fields;
+
+    if owner.trim().is_empty() {
+        return Err("Owner cannot be empty".to_string());
+    }
+
+    if let (Ok(first_day), Ok(last_day)) =
+        (appointment::parse_date(start), appointment::parse_date(end))
+    {
+        return Ok((
+            owner.trim().to_string(),
+            Appointment::new_all_day(description, first_day, last_day)?,
+        ));
+    }
+
+    let start = appointment::parse_date_time(start, DATE_TIME_FORMAT)?;
+    let end = appointment::parse_date_time(end, DATE_TIME_FORMAT)?;
+
+    Ok((
+        owner.trim().to_string(),
+        Appointment::new(description, start, end)?,
+    ))
+}
+
+// The same day n months later (or earlier), or the month's last day if it's shorter
+fn add_months(date: NaiveDate, months: i32) -> NaiveDate {
+    let index = date.year() * 12 + date.month0() as i32 + months;
+    let (year, month) = (index.div_euclid(12), index.rem_euclid(12) as u32 + 1);
+
+    (0..4)
+        .find_map(|back| NaiveDate::from_ymd_opt(year, month, date.day() - back))
+        .unwrap()
+}
+
+fn styled(text: &str, width: usize, style: &str) -> String {
+    if style.is_empty() {
+        fit(text, width)
+    } else {
+        format!("{}{}{}", style, fit(text, width), RESET)
+    }
+}
+
+// Split raw input into keys, decoding the escape sequences terminals send for special keys
+fn parse_keys(bytes: &[u8]) -> Vec<Key> {
+    let text = String::from_utf8_lossy(bytes);
+    let mut chars = text.chars().peekable();
+    let mut keys = Vec::new();
+
+    while let Some(c) = chars.next() {
+        let key = match c {
+            '\x1b' if chars.peek() == Some(&'[') || chars.peek() == Some(&'O') => {
+                chars.next();
+
+                let mut params = String::new();
+                let mut last = None;
+
+                for c in chars.by_ref() {
+                    if c.is_ascii_digit() || c == ';' {
+                        params.push(c);
+                    } else {
+                        last = Some(c);
+                        break;
+                    }
+                }
+
+                match (params.as_str(), last) {
+                    (_, Some('A')) => Key::Up,
+                    (_, Some('B')) => Key::Down,
+                    (_, Some('C')) => Key::Right,
+                    (_, Some('D')) => Key::Left,
+                    (_, Some('Z')) => Key::BackTab,
+                    ("3", Some('~')) => Key::Delete,
+                    _ => continue,
+                }
+            }
+            '\x1b' => Key::Esc,
+            '\r' | '\n' => Key::Enter,
+            '\t' => Key::Tab,
+            '\x7f' | '\x08' => Key::Backspace,
+            '\x03' => Key::Interrupt,
+            c if c.is_control() => continue,
+            c => Key::Char(c),
+        };
+
+        keys.push(key);
+    }
+
+    keys
+}
+
+fn draw(lines: &[String]) {
+    let mut out = String::from("\x1b[H");
+
+    for (index, line) in lines.iter().enumerate() {
+        if index > 0 {
+            out.push_str("\r\n");
+        }
+
+        out.push_str(line);
+        out.push_str("\x1b[K");
+    }
+
+    out.push_str("\x1b[J");
+
+    let mut stdout = io::stdout();
+    stdout.write_all(out.as_bytes()).unwrap();
+    stdout.flush().unwrap();
+}
+
+// Puts the terminal into unbuffered, no-echo mode on the alternate screen, and restores it when
+// dropped
+struct Terminal {
+    saved: String,
+}
+
+impl Terminal {
+    fn enter() -> Result<Terminal, String> {
+        let saved = stty(&["-g"])?;
+        stty(&[
+            "-icanon", "-echo", "-isig", "-ixon", "min", "1", "time", "0",
+        ])?;
+
+        // Switch to the alternate screen and hide the cursor
+        print!("\x1b[?1049h\x1b[?25l");
+
+        Ok(Terminal { saved })
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        print!("\x1b[?25h\x1b[?1049l");
+        io::stdout().flush().unwrap();
+
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> Result<String, String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()
+        .map_err(|err| format!("Failed to run stty: {}", err))?;
+
+    if !output.status.success() {
+        return Err("The terminal UI needs an interactive terminal".to_string());
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+// Rows and columns of the terminal
+fn terminal_size() -> (usize, usize) {
+    let size = stty(&["size"]).unwrap_or_default();
+
+    match size.split_whitespace().collect::<Vec<_>>().as_slice() {
+        [rows, cols] => (rows.parse().unwrap_or(24), cols.parse().unwrap_or(80)),
+        _ => (24, 80),
+    }
+}
+
+//===============================================================================================
+//===== Unit Tests
+
+#[cfg(test)]
+fn sample_book() -> Book {
+    Book::sample(&[
+        ("Alice", "Review", "10/19/2026 10:00", "10/19/2026 11:00"),
+        ("Alice", "Dentist", "10/19/2026 10:30", "10/19/2026 11:30"),
+        ("Alice", "Retro", "10/23/2026 15:00", "10/23/2026 16:00"),
+        ("Bob", "Lunch", "10/19/2026 12:00", "10/19/2026 13:00"),
+    ])
+}
+
+#[cfg(test)]
+fn plain(lines: &[String]) -> String {
+    let ansi = regex::Regex::new("\x1b\\[[0-9;]*m").unwrap();
+    ansi.replace_all(&lines.join("\n"), "").into_owned()
+}
+
+#[cfg(test)]
+fn press(app: &mut App, input: &str) {
+    for key in parse_keys(input.as_bytes()) {
+        app.handle_key(key);
+    }
+}
+
+#[test]
+fn parse_terminal_keys() {
+    assert_eq!(
+        parse_keys(b"a\x1b[A\x1b[B\x1bOC\x1b[3~\x1b\r\t\x1b[Z\x7f\x03"),
+        vec![
+            Key::Char('a'),
+            Key::Up,
+            Key::Down,
+            Key::Right,
+            Key::Delete,
+            Key::Esc,
+            Key::Enter,
+            Key::Tab,
+            Key::BackTab,
+            Key::Backspace,
+            Key::Interrupt,
+        ]
+    );
+    assert_eq!(parse_keys("é".as_bytes()), vec![Key::Char('é')]);
+}
+
+#[test]
+fn week_view_highlights_conflicts() {
+    let mut apptbook = sample_book();
+    let app = App::new(
+        &mut apptbook,
+        Hooks::default(),
+        NaiveDate::from_ymd(2026, 10, 21),
+    );
+    let screen = app.render(20, 80);
+    let text = plain(&screen);
+
+    assert!(text.starts_with(" apptbook | Alice | Week of 10/19/2026"));
+    assert!(text.contains(" Alice                | Mon 10/19/2026"));
+    assert!(text.contains(" Bob                  | ! 10:00-11:00 Review (60 min)"));
+    assert!(text.contains("! 10:30-11:30 Dentist (60 min)"));
+    assert!(text.contains("  15:00-16:00 Retro (60 min)"));
+    assert!(!text.contains("Lunch"));
+
+    // The selected conflicting appointment is reversed and red, the other just red
+    assert!(screen[2].contains("\x1b[7;31m"));
+    assert!(screen[3].contains(RED));
+
+    // Cancelled appointments don't conflict
+    let mut apptbook = sample_book();
+    apptbook.owners[0].appointments[1].status = crate::appointment::Status::Cancelled;
+    let app = App::new(
+        &mut apptbook,
+        Hooks::default(),
+        NaiveDate::from_ymd(2026, 10, 21),
+    );
+    let text = plain(&app.render(20, 80));
+    assert!(text.contains("  10:00-11:00 Review (60 min)"));
+    assert!(text.contains("  10:30-11:30 Dentist (60 min) [cancelled]"));
+}
+
+#[test]
+fn navigate_views_and_owners() {
+    let mut apptbook = sample_book();
+    let mut app = App::new(
+        &mut apptbook,
+        Hooks::default(),
+        NaiveDate::from_ymd(2026, 10, 19),
+    );
+
+    press(&mut app, "d");
+    assert_eq!(app.visible().len(), 2);
+    press(&mut app, "\x1b[C\x1b[C\x1b[C\x1b[C");
+    assert_eq!(app.visible()[0].description, "Retro");
+    press(&mut app, "m");
+    assert_eq!(app.title(), "October 2026");
+    assert_eq!(app.visible().len(), 3);
+    press(&mut app, "\tj");
+    assert_eq!(app.visible()[0].description, "Lunch");
+    press(&mut app, "\x1b[C");
+    assert_eq!(app.title(), "November 2026");
+    assert!(plain(&app.render(20, 80)).contains("No appointments"));
+
+    assert_eq!(
+        add_months(NaiveDate::from_ymd(2026, 1, 31), 1),
+        NaiveDate::from_ymd(2026, 2, 28)
+    );
+    assert_eq!(
+        add_months(NaiveDate::from_ymd(2026, 1, 15), -1),
+        NaiveDate::from_ymd(2025, 12, 15)
+    );
+}
+
+#[test]
+fn add_and_edit_all_day() {
+    let mut apptbook = sample_book();
+    let mut app = App::new(
+        &mut apptbook,
+        Hooks::default(),
+        NaiveDate::from_ymd(2026, 10, 19),
+    );
+
+    // Dates without times make an all-day appointment
+    press(&mut app, "aHoliday\t");
+    press(&mut app, &"\x7f".repeat(6));
+    press(&mut app, "\t");
+    press(&mut app, &"\x7f".repeat(16));
+    press(&mut app, "10/20/2026\r");
+    let id = app.visible()[app.selected].id;
+    let appt = &app.apptbook.find_id(id).unwrap().appt;
+    assert!(appt.all_day);
+    assert_eq!(appt.formatted_span(), "All day 10/19/2026 to 10/20/2026");
+
+    let text = plain(&app.render(20, 80));
+    assert!(text.contains("  All day Holiday (2 days)"));
+
+    // Editing shows the days and keeps the appointment all-day
+    press(&mut app, "e");
+    assert!(plain(&app.render(20, 80)).contains(" Start        10/19/2026"));
+    press(&mut app, "\r\r\r");
+    assert!(app.apptbook.find_id(id).unwrap().appt.all_day);
+}
+
+#[test]
+fn add_edit_and_delete_with_validation() {
+    let mut apptbook = sample_book();
+    let mut app = App::new(
+        &mut apptbook,
+        Hooks::default(),
+        NaiveDate::from_ymd(2026, 10, 19),
+    );
+
+    // Add with a bad start date, which is reported when leaving the field
+    press(&mut app, "aPlanning\t");
+    press(&mut app, &"\x7f".repeat(16));
+    press(&mut app, "02/30/2026 10:00\t");
+    assert!(plain(&app.render(20, 80)).contains("Invalid date. Required format: mm/dd/yyyy"));
+
+    press(&mut app, "\x1b[A");
+    press(&mut app, &"\x7f".repeat(16));
+    press(&mut app, "10/20/2026 10:00\t");
+    press(&mut app, &"\x7f".repeat(16));
+    press(&mut app, "10/20/2026 09:00");
+    press(&mut app, "\r");
+    assert!(plain(&app.render(20, 80))
+        .contains("Invalid time. End time cannot occur before start time"));
+
+    press(&mut app, &"\x7f".repeat(5));
+    press(&mut app, "11:00\r");
+    assert!(app.modified);
+    assert_eq!(app.visible()[app.selected].description, "Planning");
+
+    // Editing shows live conflicts and keeps the ID
+    let id = app.visible()[app.selected].id;
+    press(&mut app, "e\t");
+    press(&mut app, &"\x7f".repeat(16));
+    press(&mut app, "10/19/2026 10:45\t");
+    press(&mut app, &"\x7f".repeat(16));
+    press(&mut app, "10/19/2026 11:15");
+    let text = plain(&app.render(20, 80));
+    assert!(text.contains("! Conflicts with Review (10/19/2026 10:00 to 11:00)"));
+    assert!(text.contains("! Conflicts with Dentist"));
+    press(&mut app, "\r");
+    assert_eq!(
+        app.apptbook.find_id(id).unwrap().appt.duration_minutes(),
+        30
+    );
+
+    // Deleting asks first
+    let count = app.visible().len();
+    press(&mut app, "xn");
+    assert_eq!(app.visible().len(), count);
+    press(&mut app, "xy");
+    assert_eq!(app.visible().len(), count - 1);
+    assert!(app.apptbook.find_id(id).is_none());
+
+    press(&mut app, "a\x1b");
+    assert_eq!(app.message.as_deref(), Some("Canceled"));
+    press(&mut app, "q");
+    assert!(app.quit);
+}
+
+#[test]
+fn deleting_for_an_attendee_declines() {
+    use crate::attendees::{Attendee, Response};
+
+    let mut apptbook = sample_book();
+    let mut planning = Appointment::sample("Planning", "10/19/2026 09:00", "10/19/2026 09:30");
+    planning.attendees = vec![Attendee::new("Bob")];
+    let planning = apptbook.add("Alice", planning).unwrap();
+    let mut app = App::new(
+        &mut apptbook,
+        Hooks::default(),
+        NaiveDate::from_ymd(2026, 10, 19),
+    );
+
+    // Bob is only attending Planning, so it stays for Alice
+    press(&mut app, "\tj\tx");
+    assert_eq!(app.message.as_deref(), Some("Decline \"Planning\"? (y/n)"));
+    press(&mut app, "y");
+    assert_eq!(app.message.as_deref(), Some("Appointment declined"));
+    assert_eq!(app.visible().len(), 1);
+
+    let hit = app.apptbook.find_id(planning).unwrap();
+    assert_eq!(hit.owner.handle, "alice");
+    assert_eq!(hit.appt.attendees[0].response, Response::Declined);
+}